@@ -0,0 +1,253 @@
+// byoct archive create [压缩选项] [-o <archive>] [-f] <path...>
+// byoct archive list <archive>
+// byoct archive extract [--output-dir <dir>] [--table <table>] [-f] <archive> [member...]
+// 把很多文件压缩进一个归档。每个成员单独压缩成一个完整的 BYCT 容器，各自带编码表，
+// 归档末尾是中央目录，记录每个成员的路径、原始大小、偏移和压缩后的长度，
+// 列目录和解压单个成员时不需要解码其它成员。
+//
+// 格式: ARCHIVE_MAGIC | VERSION | 成员数据... | 中央目录 | 中央目录偏移(u64) | 成员个数(u32) | ARCHIVE_MAGIC
+// 中央目录的每一项: 路径长度(u32) | 路径(UTF-8，用 / 分隔) | 原始大小(u64) | 偏移(u64) | 压缩后长度(u64)
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::{compress_with, decompress, error, write_atomic, Error, Options, Progress, Table, VERSION};
+
+const ARCHIVE_MAGIC: &[u8; 4] = b"BYAR";
+const TRAILER_LEN: usize = 8 + 4 + 4;
+
+struct Entry {
+    path: String,
+    size: u64,
+    offset: u64,
+    len: u64,
+}
+
+pub fn run(args: &[String]) {
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("create") => create(&args[1..]),
+        Some("list") if args.len() == 2 => list(&args[1]),
+        Some("extract") => extract(&args[1..]),
+        _ => {
+            println!("Usage: byoct archive create [--rle] [--order1] [--words] [--preset english|code|json] [--table <table>] [-o <archive>] [-f] <path...>");
+            println!("       byoct archive list <archive>");
+            println!("       byoct archive extract [--output-dir <dir>] [--table <table>] [-f] <archive> [member...]");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = result {
+        error::exit(e);
+    }
+}
+
+fn usage(message: &str) -> Error {
+    Error::Usage(String::from(message))
+}
+
+fn create(args: &[String]) -> Result<(), Error> {
+    let mut options = Options::default();
+    let mut output = String::from("archive.byar");
+    let mut force = false;
+    let mut paths = Vec::new();
+    let mut iter = args.iter().map(|arg| arg.as_str());
+    while let Some(arg) = iter.next() {
+        if options.parse_arg(arg, &mut iter)? {
+            continue;
+        }
+        match arg {
+            "-o" | "--output" => output = iter.next().ok_or_else(|| usage("-o needs a file name"))?.to_string(),
+            "-f" | "--force" => force = true,
+            _ => paths.push(arg),
+        }
+    }
+    options.check()?;
+    if paths.is_empty() {
+        return Err(usage("No input paths"));
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(Path::new(path), &mut files)?;
+    }
+    // 成员名去掉了 .. 之类的部分，不同的路径（比如 ../x 和 x）可能得到同一个名字
+    let mut names: HashMap<&str, &Path> = HashMap::new();
+    for (path, name) in files.iter() {
+        if let Some(first) = names.insert(name, path) {
+            return Err(Error::Usage(format!("{} and {} would both be stored as {}", first.display(), path.display(), name)));
+        }
+    }
+    if Path::new(&output).exists() && !force {
+        return Err(Error::Usage(format!("{} already exists, use -f to overwrite", output)));
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(ARCHIVE_MAGIC);
+    out.push(VERSION);
+    let mut entries = Vec::new();
+    for (path, name) in files {
+        let contents = fs::read(&path).map_err(|e| Error::Io(format!("Error reading {}", path.display()), e))?;
+        let compressed = compress_with(&contents, &options, &mut Progress::default())?;
+        entries.push(Entry { path: name, size: contents.len() as u64, offset: out.len() as u64, len: compressed.len() as u64 });
+        out.extend_from_slice(&compressed);
+    }
+
+    let directory_offset = out.len() as u64;
+    for entry in entries.iter() {
+        out.extend_from_slice(&(entry.path.len() as u32).to_le_bytes());
+        out.extend_from_slice(entry.path.as_bytes());
+        out.extend_from_slice(&entry.size.to_le_bytes());
+        out.extend_from_slice(&entry.offset.to_le_bytes());
+        out.extend_from_slice(&entry.len.to_le_bytes());
+    }
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    out.extend_from_slice(ARCHIVE_MAGIC);
+    write_atomic(Path::new(&output), &out, None).map_err(|e| Error::Io(format!("Error writing {}", output), e))?;
+    println!("{} files archived to {}", entries.len(), output);
+    Ok(())
+}
+
+// 目录按文件名排序递归展开，成员名保留命令行上给出的相对路径
+fn collect_files(path: &Path, files: &mut Vec<(PathBuf, String)>) -> Result<(), Error> {
+    if path.is_dir() {
+        let mut children: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| Error::Io(format!("Error reading {}", path.display()), e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        children.sort();
+        for child in children {
+            collect_files(&child, files)?;
+        }
+        return Ok(());
+    }
+    let name: Vec<String> = path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    files.push((path.to_path_buf(), name.join("/")));
+    Ok(())
+}
+
+fn read_entries(bytes: &[u8]) -> Result<Vec<Entry>, Error> {
+    if bytes.len() < ARCHIVE_MAGIC.len() + 1 + TRAILER_LEN || !bytes.starts_with(ARCHIVE_MAGIC) || !bytes.ends_with(ARCHIVE_MAGIC) {
+        return Err(Error::Format("Not an archive"));
+    }
+    if bytes[ARCHIVE_MAGIC.len()] != VERSION {
+        return Err(Error::UnsupportedVersion(bytes[ARCHIVE_MAGIC.len()]));
+    }
+    let trailer = bytes.len() - TRAILER_LEN;
+    let mut pos = usize::try_from(read_u64(bytes, trailer)?).map_err(|_| Error::Corrupt("Invalid directory offset"))?;
+    let count = u32::from_le_bytes(bytes[trailer + 8..trailer + 12].try_into().unwrap());
+    if pos > trailer {
+        return Err(Error::Corrupt("Invalid directory offset"));
+    }
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let len = u32::from_le_bytes(bytes.get(pos..pos + 4).ok_or(Error::Corrupt("Truncated directory"))?.try_into().unwrap()) as usize;
+        pos += 4;
+        let path = bytes.get(pos..pos + len).ok_or(Error::Corrupt("Truncated directory"))?;
+        let path = String::from_utf8(path.to_vec()).map_err(|_| Error::Corrupt("Invalid UTF-8 in path"))?;
+        pos += len;
+        let entry = Entry { path, size: read_u64(bytes, pos)?, offset: read_u64(bytes, pos + 8)?, len: read_u64(bytes, pos + 16)? };
+        pos += 24;
+        if entry.offset.checked_add(entry.len).is_none_or(|end| end > trailer as u64) {
+            return Err(Error::Corrupt("Member outside archive"));
+        }
+        entries.push(entry);
+    }
+    if pos > trailer {
+        return Err(Error::Corrupt("Truncated directory"));
+    }
+    Ok(entries)
+}
+
+fn read_u64(bytes: &[u8], pos: usize) -> Result<u64, Error> {
+    let value = bytes.get(pos..pos.checked_add(8).ok_or(Error::Corrupt("Truncated directory"))?).ok_or(Error::Corrupt("Truncated directory"))?;
+    Ok(u64::from_le_bytes(value.try_into().unwrap()))
+}
+
+fn list(archive: &str) -> Result<(), Error> {
+    let bytes = fs::read(archive).map_err(|e| Error::Io(format!("Error reading {}", archive), e))?;
+    let entries = read_entries(&bytes)?;
+    println!("{:>12} {:>12} {:>7}  path", "size", "compressed", "ratio");
+    let (mut size, mut len) = (0, 0);
+    for entry in entries.iter() {
+        println!("{:>12} {:>12} {:>6.1}%  {}", entry.size, entry.len, ratio(entry.len, entry.size), entry.path);
+        size += entry.size;
+        len += entry.len;
+    }
+    println!("{:>12} {:>12} {:>6.1}%  {} files", size, len, ratio(len, size), entries.len());
+    Ok(())
+}
+
+fn ratio(compressed: u64, size: u64) -> f64 {
+    if size == 0 { 0.0 } else { compressed as f64 * 100.0 / size as f64 }
+}
+
+fn extract(args: &[String]) -> Result<(), Error> {
+    let mut output_dir = PathBuf::from(".");
+    let mut tables = Vec::new();
+    let mut force = false;
+    let mut archive = None;
+    let mut members = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--output-dir" => output_dir = PathBuf::from(iter.next().ok_or_else(|| usage("--output-dir needs a directory"))?),
+            "--table" => tables.push(Table::load(Path::new(iter.next().ok_or_else(|| usage("--table needs a table file"))?))?),
+            "-f" | "--force" => force = true,
+            _ if archive.is_none() => archive = Some(arg),
+            _ => members.push(arg.as_str()),
+        }
+    }
+    let archive = archive.ok_or_else(|| usage("No archive given"))?;
+    let bytes = fs::read(archive).map_err(|e| Error::Io(format!("Error reading {}", archive), e))?;
+    let entries = read_entries(&bytes)?;
+
+    for member in members.iter() {
+        if !entries.iter().any(|entry| entry.path == *member) {
+            return Err(Error::Usage(format!("{} is not in {}", member, archive)));
+        }
+    }
+    let selected: Vec<&Entry> = entries.iter().filter(|entry| members.is_empty() || members.contains(&entry.path.as_str())).collect();
+    // 写任何文件之前先检查，不会只解出一半
+    for entry in selected.iter() {
+        let path = safe_path(&output_dir, &entry.path)?;
+        if path.exists() && !force {
+            return Err(Error::Usage(format!("{} already exists, use -f to overwrite", path.display())));
+        }
+    }
+    for entry in selected {
+        let path = safe_path(&output_dir, &entry.path)?;
+        let data = &bytes[entry.offset as usize..(entry.offset + entry.len) as usize];
+        let contents = decompress(data, &tables).inspect_err(|_| eprintln!("byoct: error decoding {}", entry.path))?;
+        if contents.len() as u64 != entry.size {
+            eprintln!("byoct: {}: decoded {} bytes, directory says {}", entry.path, contents.len(), entry.size);
+            return Err(Error::Corrupt("Member size does not match directory"));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::Io(format!("Error creating {}", parent.display()), e))?;
+        }
+        write_atomic(&path, &contents, None).map_err(|e| Error::Io(format!("Error writing {}", path.display()), e))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+// 成员路径只能是相对路径，不能有 ..，否则可能写到输出目录之外
+fn safe_path(output_dir: &Path, member: &str) -> Result<PathBuf, Error> {
+    let mut path = output_dir.to_path_buf();
+    for part in member.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains('\\') || Path::new(part).has_root() {
+            eprintln!("byoct: unsafe path in archive: {}", member);
+            return Err(Error::Corrupt("Unsafe member path"));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
@@ -0,0 +1,367 @@
+// byoct bench [--size N] [--iterations K] [file...]
+// 比较仓库里的各个建树实现：build_huffman_tree_*.rs 六个版本和 byoct1.rs 自己的
+// sort_by_key/remove(0) 循环。每个版本的源文件原样 include 进来，
+// 只计时它自己的建树函数，然后把树转换成 HuffmanNode，
+// 用同一套 encode_contents/decode 测编码和解码的速度，这样速度的差别只来自树的形状。
+// 测试数据是均匀分布、Zipf 分布、Fibonacci 分布，以及命令行给出的文件（默认 ../135-0.txt）。
+//
+// byoct bench --count [--size N] [--threads N] [file...]
+// 频率统计的吞吐量：原来串行写一个 HashMap 的做法，和 count.rs 用 1、2、4... 直到 --threads（默认核数）个线程
+// 按字符、按字节统计。
+// 文件用 mmap 读入，可以直接测几 GB 的文件；不给文件时生成 --size 字节（默认 256 MB）的随机文本。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{build_hashmap, build_huffman_codes, build_huffman_tree, count, decode, encode_contents, error, mmap, Error, HuffmanNode};
+
+// 合成数据的字母表从 '!' 开始，避开 gemini 版本用作内部节点标记的 '\0'，
+// 并且不超过 255，comate 版本只支持字节
+const FIRST_SYMBOL: u32 = 0x21;
+const ALPHABET_SIZE: u32 = 200;
+const FIBONACCI_SYMBOLS: usize = 25;
+
+fn leaf(ch: char, freq: usize) -> HuffmanNode {
+    HuffmanNode { ch: Some(ch), freq, left: None, right: None }
+}
+
+fn parent(freq: usize, left: HuffmanNode, right: HuffmanNode) -> HuffmanNode {
+    HuffmanNode { ch: None, freq, left: Some(Box::new(left)), right: Some(Box::new(right)) }
+}
+
+// 每个模块提供 build(频率, 原文) -> Option<(建树耗时, 转换后的树)>，不支持的输入返回 None
+
+mod byoct {
+    use super::*;
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(Duration, HuffmanNode)> {
+        let start = Instant::now();
+        let root = build_huffman_tree(frequencies);
+        Some((start.elapsed(), *root))
+    }
+}
+
+mod comate {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_comate.rs");
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let mut bytes = HashMap::new();
+        for (ch, freq) in frequencies {
+            bytes.insert(u8::try_from(*ch as u32).ok()?, *freq);
+        }
+        let start = std::time::Instant::now();
+        let root = build_huffman_tree(&bytes);
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &HuffmanNode) -> crate::HuffmanNode {
+        match (node.character, &node.left, &node.right) {
+            (Some(byte), _, _) => super::leaf(char::from(byte), node.frequency),
+            (None, Some(left), Some(right)) => super::parent(node.frequency, convert(left), convert(right)),
+            _ => panic!("Invalid node"),
+        }
+    }
+}
+
+mod fittencode {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_fittencode.rs");
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let start = std::time::Instant::now();
+        let root = build_tree(frequencies)?;
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &Node) -> crate::HuffmanNode {
+        match (node.data, &node.left, &node.right) {
+            (Some(ch), _, _) => super::leaf(ch, node.frequency),
+            (None, Some(left), Some(right)) => super::parent(node.frequency, convert(left), convert(right)),
+            _ => panic!("Invalid node"),
+        }
+    }
+}
+
+mod gemini {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_gemini.rs");
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let mut symbols = HashMap::new();
+        for (ch, freq) in frequencies {
+            if *ch == '\0' {
+                return None;
+            }
+            symbols.insert(*ch, u32::try_from(*freq).ok()?);
+        }
+        let start = std::time::Instant::now();
+        let root = build_huffman_tree(&symbols);
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &Node) -> crate::HuffmanNode {
+        match (&node.left, &node.right) {
+            (Some(left), Some(right)) => super::parent(node.frequency as usize, convert(left), convert(right)),
+            _ => super::leaf(node.symbol, node.frequency as usize),
+        }
+    }
+}
+
+mod kimi {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_kimi.rs");
+
+    // kimi 版本自己统计频率，计时包括统计
+    pub fn build(_: &HashMap<char, usize>, text: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let start = std::time::Instant::now();
+        let root = build_huffman_tree(text);
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &Node) -> crate::HuffmanNode {
+        match (node.character, &node.left, &node.right) {
+            (Some(ch), _, _) => super::leaf(ch, node.frequency),
+            (None, Some(left), Some(right)) => super::parent(node.frequency, convert(left), convert(right)),
+            _ => panic!("Invalid node"),
+        }
+    }
+}
+
+mod tongyilingma {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_tongyilingma.rs");
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let frequencies = frequencies.clone();
+        let start = std::time::Instant::now();
+        let root = build_huffman_tree(frequencies);
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &Node) -> crate::HuffmanNode {
+        match (node.character, &node.left, &node.right) {
+            (Some(ch), _, _) => super::leaf(ch, node.frequency),
+            (None, Some(left), Some(right)) => super::parent(node.frequency, convert(left), convert(right)),
+            _ => panic!("Invalid node"),
+        }
+    }
+}
+
+mod yiyan {
+    #![allow(dead_code)]
+    include!("build_huffman_tree_yiyan.rs");
+
+    pub fn build(frequencies: &HashMap<char, usize>, _: &str) -> Option<(std::time::Duration, crate::HuffmanNode)> {
+        let start = std::time::Instant::now();
+        let root = build_huffman_tree(frequencies);
+        Some((start.elapsed(), convert(&root)))
+    }
+
+    fn convert(node: &HuffmanNode) -> crate::HuffmanNode {
+        match (node.symbol, &node.left, &node.right) {
+            (Some(ch), _, _) => super::leaf(ch, node.weight),
+            (None, Some(left), Some(right)) => super::parent(node.weight, convert(left), convert(right)),
+            _ => panic!("Invalid node"),
+        }
+    }
+}
+
+type Builder = fn(&HashMap<char, usize>, &str) -> Option<(Duration, HuffmanNode)>;
+
+const BUILDERS: &[(&str, Builder)] = &[
+    ("byoct1", byoct::build),
+    ("comate", comate::build),
+    ("fittencode", fittencode::build),
+    ("gemini", gemini::build),
+    ("kimi", kimi::build),
+    ("tongyilingma", tongyilingma::build),
+    ("yiyan", yiyan::build),
+];
+
+pub fn run(args: &[String]) {
+    let mut size = None;
+    let mut iterations = 20;
+    let mut count_mode = false;
+    let mut max_threads = count::threads();
+    let mut files = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--size" => size = iter.next().and_then(|n| n.parse().ok()).or(size),
+            "--iterations" => iterations = iter.next().and_then(|n| n.parse().ok()).unwrap_or(iterations),
+            "--count" => count_mode = true,
+            "--threads" => max_threads = iter.next().and_then(|n| n.parse().ok()).unwrap_or(max_threads),
+            _ => files.push(arg.clone()),
+        }
+    }
+    if count_mode {
+        return run_count(&files, size.unwrap_or(1 << 28), max_threads.max(1));
+    }
+    let size = size.unwrap_or(1_000_000);
+    if files.is_empty() && Path::new("../135-0.txt").exists() {
+        files.push(String::from("../135-0.txt"));
+    }
+
+    let mut datasets = vec![
+        (String::from("uniform"), synthetic(&uniform_counts(size))),
+        (String::from("zipf"), synthetic(&zipf_counts(size))),
+        (String::from("fibonacci"), synthetic(&fibonacci_counts())),
+    ];
+    for file in files {
+        let contents = fs::read_to_string(&file).unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", file), e)));
+        datasets.push((file, contents));
+    }
+
+    println!("{:<14} {:<13} {:>12} {:>10} {:>12} {:>12}", "dataset", "builder", "build (us)", "bits/sym", "encode MB/s", "decode MB/s");
+    for (name, text) in datasets.iter() {
+        let frequencies = build_hashmap(text);
+        for (builder, build) in BUILDERS {
+            let mut best = Duration::MAX;
+            let mut root = None;
+            for _ in 0..iterations.max(1) {
+                match build(&frequencies, text) {
+                    Some((elapsed, tree)) => {
+                        best = best.min(elapsed);
+                        root = Some(Box::new(tree));
+                    },
+                    None => break,
+                }
+            }
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    println!("{:<14} {:<13} {:>12}", name, builder, "n/a");
+                    continue;
+                },
+            };
+
+            let codes = build_huffman_codes(&root);
+            let start = Instant::now();
+            let bits = encode_contents(text, &codes);
+            let encode_time = start.elapsed();
+            let start = Instant::now();
+            let decoded = decode(&bits, &root).expect("Error decoding");
+            let decode_time = start.elapsed();
+            assert!(decoded == *text, "{} produced a tree that does not round-trip", builder);
+
+            let megabytes = text.len() as f64 / 1_000_000.0;
+            println!("{:<14} {:<13} {:>12.1} {:>10.4} {:>12.1} {:>12.1}", name, builder,
+                best.as_secs_f64() * 1_000_000.0, bits.len() as f64 / text.chars().count() as f64,
+                megabytes / encode_time.as_secs_f64(), megabytes / decode_time.as_secs_f64());
+        }
+    }
+}
+
+fn uniform_counts(size: usize) -> Vec<usize> {
+    vec![size / ALPHABET_SIZE as usize; ALPHABET_SIZE as usize]
+}
+
+// 第 k 个符号的频率正比于 1/k
+fn zipf_counts(size: usize) -> Vec<usize> {
+    let harmonic: f64 = (1..=ALPHABET_SIZE).map(|k| 1.0 / k as f64).sum();
+    (1..=ALPHABET_SIZE).map(|k| ((size as f64 / harmonic / k as f64) as usize).max(1)).collect()
+}
+
+// Fibonacci 频率会让 Huffman 树退化成一条链，是最深的情况
+fn fibonacci_counts() -> Vec<usize> {
+    let mut counts = vec![1, 1];
+    while counts.len() < FIBONACCI_SYMBOLS {
+        counts.push(counts[counts.len() - 1] + counts[counts.len() - 2]);
+    }
+    counts
+}
+
+// 按给定的频率生成文本，再用固定种子的 xorshift 打乱，每次运行结果一样
+fn synthetic(counts: &[usize]) -> String {
+    let mut symbols = Vec::new();
+    for (i, count) in counts.iter().enumerate() {
+        let ch = char::from_u32(FIRST_SYMBOL + i as u32).unwrap();
+        symbols.extend(std::iter::repeat_n(ch, *count));
+    }
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for i in (1..symbols.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        symbols.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    symbols.into_iter().collect()
+}
+
+// 改成多线程之前 build_hashmap 的做法，作为对照
+fn serial_hashmap(text: &str) -> HashMap<char, usize> {
+    let mut frequencies = HashMap::new();
+    for ch in text.chars() {
+        *frequencies.entry(ch).or_insert(0) += 1;
+    }
+    frequencies
+}
+
+fn run_count(files: &[String], size: usize, max_threads: usize) {
+    let mut datasets: Vec<(String, mmap::Input)> = Vec::new();
+    for file in files {
+        let contents = mmap::read(Path::new(file), true).unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", file), e)));
+        datasets.push((file.clone(), contents));
+    }
+    if datasets.is_empty() {
+        // 可打印的 ASCII，低位偏多，近似文本的分布
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let bytes = (0..size).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (FIRST_SYMBOL as u64 + (state % 95) * (state >> 32 & 0xff) / 255) as u8
+        }).collect();
+        datasets.push((format!("random {} MB", size >> 20), mmap::Input::Owned(bytes)));
+    }
+
+    let mut thread_counts = Vec::new();
+    let mut n = 1;
+    while n < max_threads {
+        thread_counts.push(n);
+        n *= 2;
+    }
+    thread_counts.push(max_threads);
+
+    println!("{:<20} {:<8} {:>8} {:>10} {:>8}", "dataset", "method", "threads", "MB/s", "speedup");
+    for (name, bytes) in datasets.iter().filter(|(_, bytes)| !bytes.is_empty()) {
+        let megabytes = bytes.len() as f64 / 1_000_000.0;
+        let text = std::str::from_utf8(bytes).ok();
+        let mut expected = None;
+        if let Some(text) = text {
+            let start = Instant::now();
+            expected = Some(serial_hashmap(text));
+            println!("{:<20} {:<8} {:>8} {:>10.1} {:>8}", name, "hashmap", 1, megabytes / start.elapsed().as_secs_f64(), "");
+        }
+
+        for method in ["text", "bytes"] {
+            if method == "text" && text.is_none() {
+                println!("{:<20} {:<8} {:>8}", name, method, "n/a");
+                continue;
+            }
+            let mut single = 0.0;
+            for &threads in thread_counts.iter() {
+                let start = Instant::now();
+                let frequencies = match method {
+                    "text" => count::count_text(text.unwrap(), threads),
+                    _ => count::count_bytes(bytes, threads),
+                };
+                let elapsed = start.elapsed().as_secs_f64();
+                let frequencies = frequencies.into_hashmap();
+                if method == "text" {
+                    assert!(Some(&frequencies) == expected.as_ref(), "count_text disagrees with the serial count");
+                } else {
+                    assert!(frequencies.values().sum::<usize>() == bytes.len(), "count_bytes lost bytes");
+                }
+                let throughput = megabytes / elapsed;
+                if threads == 1 {
+                    single = throughput;
+                }
+                println!("{:<20} {:<8} {:>8} {:>10.1} {:>7.2}x", name, method, threads, throughput, throughput / single);
+            }
+        }
+    }
+}
@@ -91,8 +91,8 @@ fn main() {
     println!("Encoded Data: {}", encoded_data);
 }
 
-Huffman Codes:
-c (99): 01
-a (97): 1
-b (98): 00
-Encoded Data: 100011001011000111
\ No newline at end of file
+// Huffman Codes:
+// c (99): 01
+// a (97): 1
+// b (98): 00
+// Encoded Data: 100011001011000111
\ No newline at end of file
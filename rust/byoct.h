@@ -0,0 +1,93 @@
+// 由 test/capi.rs 从 capi.rs 生成，不要手改
+// C 接口，给 C/C++ 的服务直接调用压缩器。压缩用默认选项（和不带选项的 byoct compress 一样），
+// 解压认所有格式，包括可随机访问的格式；用 --table 压缩的数据这里解不了。
+// 流式的句柄并不流式：容器格式要看完整个输入才能建树，编码器和解码器都把写进来的数据全部攒在内存里，
+// finish 时一次处理完，之后才能读输出。解码器也不能边收边解，内存至少是压缩数据加上解压后的数据。
+// 函数里的 panic 都被拦下来，返回 BYOCT_ERROR_INTERNAL，不会展开到 C 代码里。
+
+#ifndef BYOCT_H
+#define BYOCT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// 成功
+#define BYOCT_OK 0
+// 参数不对：空指针，或者流式句柄的调用顺序不对
+#define BYOCT_ERROR_ARGUMENT 1
+// 输入不是压缩数据
+#define BYOCT_ERROR_FORMAT 2
+// 压缩数据损坏
+#define BYOCT_ERROR_CORRUPT 3
+// 格式版本或功能不支持，通常是更新的版本写的数据
+#define BYOCT_ERROR_UNSUPPORTED 4
+// 超出实现的限制，比如树太深
+#define BYOCT_ERROR_LIMIT 5
+// 数据是用共享编码表（byoct compress --table）压缩的
+#define BYOCT_ERROR_MISSING_TABLE 6
+// 输出缓冲区不够，*dst_len 是需要的长度
+#define BYOCT_ERROR_BUFFER_TOO_SMALL 7
+// 内部错误，不应该出现
+#define BYOCT_ERROR_INTERNAL 8
+
+// 压缩 src_len 字节最多输出多少字节。
+// 数据部分不超过输入：Huffman 码不比把 UTF-8（或者按字节编码时的 8 位定长码）当作前缀码更长。
+// 树每个叶子最多 38 字节加频率的位数，每个内部节点最多 26 字节加频率的位数，叶子不超过输入的字节数
+size_t byoct_compress_bound(size_t src_len);
+
+// 用默认选项压缩 src 的 src_len 字节，写到 dst 里，*dst_len 是压缩后的长度。
+// dst_cap 至少是 byoct_compress_bound(src_len) 时一定放得下
+int byoct_compress(const uint8_t *src, size_t src_len, uint8_t *dst, size_t dst_cap, size_t *dst_len);
+
+// 解压 src 的 src_len 字节，写到 dst 里，*dst_len 是解压后的长度。
+// 放不下时返回 BYOCT_ERROR_BUFFER_TOO_SMALL，*dst_len 是需要的长度
+int byoct_decompress(const uint8_t *src, size_t src_len, uint8_t *dst, size_t dst_cap, size_t *dst_len);
+
+// 错误码的说明，返回的字符串是静态的，不用释放
+const char *byoct_error_string(int code);
+
+// 流式编码器，不透明的句柄。写进来的数据全部留在内存里，finish 之前没有任何输出
+typedef struct byoct_encoder byoct_encoder;
+
+// 流式解码器，不透明的句柄。写进来的压缩数据全部留在内存里，finish 之前不解码，不能边收边解
+typedef struct byoct_decoder byoct_decoder;
+
+// 新建编码器，用完要 byoct_encoder_free
+byoct_encoder *byoct_encoder_new(void);
+
+// 追加要压缩的数据，finish 之后不能再写
+int byoct_encoder_write(byoct_encoder *encoder, const uint8_t *src, size_t src_len);
+
+// 压缩写进来的所有数据，之后可以读输出
+int byoct_encoder_finish(byoct_encoder *encoder);
+
+// 读最多 dst_cap 字节压缩后的数据，*dst_len 是读到的长度，读完之后是 0
+int byoct_encoder_read(byoct_encoder *encoder, uint8_t *dst, size_t dst_cap, size_t *dst_len);
+
+// 释放编码器，空指针什么也不做
+void byoct_encoder_free(byoct_encoder *encoder);
+
+// 新建解码器，用完要 byoct_decoder_free
+byoct_decoder *byoct_decoder_new(void);
+
+// 追加压缩数据，finish 之后不能再写
+int byoct_decoder_write(byoct_decoder *decoder, const uint8_t *src, size_t src_len);
+
+// 解压写进来的所有数据，之后可以读输出。出错之后可以接着写，再 finish 一次
+int byoct_decoder_finish(byoct_decoder *decoder);
+
+// 读最多 dst_cap 字节解压后的数据，*dst_len 是读到的长度，读完之后是 0
+int byoct_decoder_read(byoct_decoder *decoder, uint8_t *dst, size_t dst_cap, size_t *dst_len);
+
+// 释放解码器，空指针什么也不做
+void byoct_decoder_free(byoct_decoder *decoder);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -1,9 +1,103 @@
+extern crate alloc;
+
 use std::env;
 use std::fs;
-use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::process;
+use std::collections::{HashMap, VecDeque};
 // use std::collections::BinaryHeap;
 // use std::cmp::Reverse;
-// use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use error::Error;
+use metadata::Metadata;
+use progress::{Phase, Progress};
+use table::Table;
+
+mod archive;
+mod bench;
+mod codes;
+mod count;
+mod error;
+mod fuzz;
+mod huffman;
+mod metadata;
+mod mmap;
+mod context;
+mod presets;
+mod progress;
+mod rle;
+mod seekable;
+mod stats;
+mod table;
+mod trace;
+mod tree;
+mod verify;
+mod words;
+
+const MAGIC: &[u8; 4] = b"BYCT";
+// 压缩文件的后缀
+const SUFFIX: &str = "byoct";
+const VERSION: u8 = 1;
+// flags 中的各个位
+const FLAG_RLE: u8 = 0x01;
+const FLAG_ORDER1: u8 = 0x02;
+const FLAG_WORDS: u8 = 0x04;
+const FLAG_PRESET: u8 = 0x08;
+// 输入不是合法的 UTF-8，按字节编码，每个字节当作码点 0..=255 的字符
+const FLAG_BYTES: u8 = 0x10;
+// flags 后面紧跟着元数据（文件名、权限、修改时间），见 metadata.rs
+const FLAG_METADATA: u8 = 0x20;
+// 用训练好的共享编码表（--table）编码，树这一段只有表的哈希，见 table.rs
+const FLAG_TABLE: u8 = 0x40;
+const KNOWN_FLAGS: u8 = FLAG_RLE | FLAG_ORDER1 | FLAG_WORDS | FLAG_PRESET | FLAG_BYTES | FLAG_METADATA | FLAG_TABLE;
+// 频率不超过 u64 时 Huffman 树最深 92 层（Fibonacci 分布），
+// 解析时超过这个深度的树一定是损坏的，也避免递归太深导致栈溢出
+const MAX_TREE_DEPTH: usize = 128;
+
+// 压缩时可选的各种变换
+#[derive(Default)]
+struct Options {
+    rle: bool,
+    order1: bool,
+    words: bool,
+    preset: Option<u8>,
+    table: Option<Table>,
+    metadata: Option<Metadata>,
+}
+
+impl Options {
+    // 解析一个压缩选项，需要参数的选项从 rest 里取；arg 不是压缩选项时返回 Ok(false)
+    fn parse_arg<'a>(&mut self, arg: &str, rest: &mut impl Iterator<Item = &'a str>) -> Result<bool, Error> {
+        match arg {
+            "--rle" => self.rle = true,
+            "--order1" => self.order1 = true,
+            "--words" => self.words = true,
+            "--preset" => {
+                let name = rest.next().ok_or_else(|| Error::Usage(String::from("--preset needs a preset name")))?;
+                let id = presets::preset_id(name)
+                    .ok_or_else(|| Error::Usage(format!("Unknown preset '{}', expected english, code or json", name)))?;
+                self.preset = Some(id);
+            },
+            "--table" => {
+                let path = rest.next().ok_or_else(|| Error::Usage(String::from("--table needs a table file")))?;
+                self.table = Some(Table::load(Path::new(path))?);
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.preset.is_some() && (self.order1 || self.words) {
+            return Err(Error::Usage(String::from("--preset cannot be combined with --order1 or --words")));
+        }
+        if self.table.is_some() && (self.preset.is_some() || self.order1 || self.words) {
+            return Err(Error::Usage(String::from("--table cannot be combined with --preset, --order1 or --words")));
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug)]
 struct HuffmanNode {
@@ -26,7 +120,14 @@ impl HuffmanNode {
     fn serialize(&self, out: &mut Vec<u8>) {
         out.extend_from_slice(b"{");
         if let Some(ch) = self.ch {
-            out.extend(format!("\"ch\":\"{}\",", ch).as_bytes());
+            out.extend_from_slice(b"\"ch\":\"");
+            // 引号和反斜杠需要转义，否则解析时会提前结束
+            if ch == '"' || ch == '\\' {
+                out.push(b'\\');
+            }
+            let mut buf = [0; 4];
+            out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            out.extend_from_slice(b"\",");
         }
         out.extend_from_slice(b"\"freq\":");
         out.extend_from_slice(&format!("{}", self.freq).as_bytes());
@@ -37,95 +138,125 @@ impl HuffmanNode {
         out.extend_from_slice(b"}");
     }
 
-    fn deserialize(mut bytes: &[u8]) -> Result<Self, &'static str> {
-        if !bytes.starts_with(b"{") || !bytes.ends_with(b"}") {
-            return Err("Invalid node format");
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let text = std::str::from_utf8(bytes).map_err(|_| Error::Corrupt("Invalid UTF-8 in tree"))?;
+        let mut chars = text.chars();
+        let node = Self::parse_node(&mut chars, 0)?;
+        if chars.next().is_some() {
+            return Err(Error::Corrupt("Trailing data after tree"));
+        }
+        Ok(node)
+    }
+
+    // 递归解析一个 {"ch":"x","freq":1,"left":{...},"right":{...}} 节点
+    fn parse_node(chars: &mut std::str::Chars, depth: usize) -> Result<Self, Error> {
+        if depth > MAX_TREE_DEPTH {
+            return Err(Error::Limit("Tree too deep"));
+        }
+        if chars.next() != Some('{') {
+            return Err(Error::Corrupt("Invalid node format"));
         }
-        // bytes = &bytes[1..bytes.len()-1]; // remove curly braces
-        // 修正为使用切片来跳过处理过的数据
-        bytes = &bytes[Self::right_after_value(bytes)..];
 
         let mut ch: Option<char> = None;
         let mut freq: usize = 0;
         let mut left: Option<Box<HuffmanNode>> = None;
         let mut right: Option<Box<HuffmanNode>> = None;
 
-        let mut key = String::new();
-        let mut value = String::new();
-        let mut parsing_key = true;
-        let mut parsing_value = false;
-        let mut in_quotes = false;
-
-        for byte in bytes {
-            match byte {
-                b',' | b':' | b'}' => {
-                    if !in_quotes {
-                        if parsing_key {
-                            parsing_key = false;
-                            parsing_value = true;
+        loop {
+            if chars.next() != Some('"') {
+                return Err(Error::Corrupt("Expected key"));
+            }
+            let mut key = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => key.push(c),
+                    None => return Err(Error::Corrupt("Unterminated key")),
+                }
+            }
+            if chars.next() != Some(':') {
+                return Err(Error::Corrupt("Expected ':'"));
+            }
+
+            match key.as_str() {
+                "ch" => {
+                    if chars.next() != Some('"') {
+                        return Err(Error::Corrupt("Expected quoted char"));
+                    }
+                    let c = match chars.next() {
+                        Some('\\') => chars.next(),
+                        c => c,
+                    };
+                    ch = Some(c.ok_or(Error::Corrupt("Unterminated char"))?);
+                    if chars.next() != Some('"') {
+                        return Err(Error::Corrupt("Expected closing quote"));
+                    }
+                },
+                "freq" => {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.clone().next().filter(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                        chars.next();
+                    }
+                    freq = digits.parse::<usize>().map_err(|_| Error::Corrupt("Invalid freq"))?;
+                },
+                "left" | "right" => {
+                    // 叶子节点的 left/right 为空
+                    if chars.clone().next() == Some('{') {
+                        let child = Some(Box::new(Self::parse_node(chars, depth + 1)?));
+                        if key == "left" {
+                            left = child;
                         } else {
-                            parsing_key = true;
-                            parsing_value = false;
+                            right = child;
                         }
                     }
                 },
-                b'"' => in_quotes = !in_quotes,
-                _ if parsing_key => key.push(char::from(*byte)),
-                _ if parsing_value => value.push(char::from(*byte)),
-                _ => (), // ignore other characters
+                _ => return Err(Error::Corrupt("Unknown key")),
             }
 
-            if parsing_key && key == "ch" {
-                parsing_key = false;
-                parsing_value = true;
-                key.clear();
-            } else if parsing_key && key == "freq" {
-                parsing_key = false;
-                parsing_value = true;
-                key.clear();
-            } else if parsing_key && key == "left" {
-                parsing_key = false;
-                parsing_value = true;
-                key.clear();
-                left = Some(Box::new(HuffmanNode::deserialize(bytes)?));
-                bytes = &bytes[Self::right_after_value(bytes)];
-            } else if parsing_key && key == "right" {
-                parsing_key = false;
-                parsing_value = true;
-                key.clear();
-                right = Some(Box::new(HuffmanNode::deserialize(bytes)?));
-                bytes = &bytes[Self::right_after_value(bytes)];
-            } else if parsing_value && value.parse::<usize>().is_ok() {
-                parsing_key = true;
-                parsing_value = false;
-                freq = value.parse::<usize>().unwrap();
-                value.clear();
-            } else if parsing_value && ch.is_none() {
-                parsing_key = true;
-                parsing_value = false;
-                ch = Some(value.parse::<char>().unwrap());
-                value.clear();
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(Error::Corrupt("Expected ',' or '}'")),
             }
         }
 
+        // 叶子没有孩子，内部节点两个孩子都要有，否则解码时会走到空指针
+        if ch.is_some() != (left.is_none() && right.is_none()) || left.is_some() != right.is_some() {
+            return Err(Error::Corrupt("Invalid node"));
+        }
         Ok(HuffmanNode { ch, freq, left, right })
     }
 
-    fn right_after_value(bytes: &[u8]) -> usize {
-        for (i, byte) in bytes.iter().enumerate() {
-            if *byte == b',' || *byte == b'}' {
-                return i+1;
-            }
+    // no_std 核心（huffman.rs）的树从 index 开始转换成 HuffmanNode，用来序列化
+    fn from_codes(codes: &huffman::Codes, index: u16) -> HuffmanNode {
+        match codes.node(index) {
+            (freq, Some(byte), _) => HuffmanNode::new(char::from(byte), freq),
+            (freq, None, children) => {
+                let [left, right] = children.unwrap();
+                let (left, right) = (Self::from_codes(codes, left), Self::from_codes(codes, right));
+                HuffmanNode { ch: None, freq, left: Some(Box::new(left)), right: Some(Box::new(right)) }
+            },
         }
-        bytes.len()
     }
-    fn left_after_value(bytes: &[u8]) -> usize {
-        for (i, byte) in bytes.iter().enumerate() {
-            if *byte == b',' {
-                return i;
+
+    // 转换成核心的码表，叶子必须是字节
+    fn to_codes(&self) -> Result<huffman::Codes, Error> {
+        fn collect(node: &HuffmanNode, code: u128, len: u8, entries: &mut Vec<(u8, u128, u8)>) -> Result<(), Error> {
+            match (node.ch, &node.left, &node.right) {
+                // 只有一种字节时根节点就是叶子，码是 "0"
+                (Some(ch), _, _) => entries.push((u8::try_from(ch).map_err(|_| Error::Corrupt("Invalid byte"))?, code, len.max(1))),
+                (None, Some(left), Some(right)) => {
+                    collect(left, code << 1, len + 1, entries)?;
+                    collect(right, code << 1 | 1, len + 1, entries)?;
+                },
+                _ => return Err(Error::Corrupt("Invalid tree")),
             }
+            Ok(())
         }
-        bytes.len()
+        let mut entries = Vec::new();
+        collect(self, 0, 0, &mut entries)?;
+        Ok(huffman::Codes::from_codes(entries)?)
     }
 }
 
@@ -140,88 +271,651 @@ fn main(){
     // 获取命令行参数
     let args: Vec<String> = env::args().collect();
 
-    // 检查参数数量
-    if args.len() <2 {
-        println!("Usage: {} <input_file>", args[0]);
-        return;
+    // 子命令
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("stats") => return stats::run(&args[2..]),
+        Some("codes") => return codes::run(&args[2..]),
+        Some("tree") => return tree::run(&args[2..]),
+        Some("bench") => return bench::run(&args[2..]),
+        Some("fuzz") => return fuzz::run(&args[2..]),
+        Some("verify") => return verify::run(&args[2..]),
+        Some("archive") => return archive::run(&args[2..]),
+        Some("train") => return table::run(&args[2..]),
+        _ => (),
+    }
+
+    // compress/decompress 明确指定方向，否则根据 MAGIC 自动判断
+    let (mode, rest) = match args.get(1).map(|arg| arg.as_str()) {
+        Some("compress") => (Some(true), &args[2..]),
+        Some("decompress") => (Some(false), &args[2..]),
+        _ => (None, &args[1..]),
+    };
+
+    let mut options = Options::default();
+    let mut input_file = None;
+    let mut output_file = None;
+    let mut output_dir = None;
+    let mut to_stdout = false;
+    let mut no_name = false;
+    let mut restore_name = false;
+    let mut keep = false;
+    let mut force = false;
+    let mut use_mmap = false;
+    let mut seekable = false;
+    let mut block_size = seekable::DEFAULT_BLOCK_SIZE;
+    let mut block_tables = false;
+    let mut range = None;
+    let mut iter = rest.iter().map(|arg| arg.as_str());
+    while let Some(arg) = iter.next() {
+        match options.parse_arg(arg, &mut iter) {
+            Ok(true) => continue,
+            Ok(false) => (),
+            Err(e) => error::exit(e),
+        }
+        match arg {
+            "-o" | "--output" => match iter.next() {
+                Some(name) => output_file = Some(name),
+                None => error::exit(Error::Usage(format!("{} needs a file name", arg))),
+            },
+            "--output-dir" => match iter.next() {
+                Some(dir) => output_dir = Some(dir),
+                None => error::exit(Error::Usage(String::from("--output-dir needs a directory"))),
+            },
+            "-c" | "--stdout" => to_stdout = true,
+            "-n" | "--no-name" => no_name = true,
+            "-N" | "--name" => restore_name = true,
+            "-k" | "--keep" => keep = true,
+            "-f" | "--force" => force = true,
+            "--mmap" => use_mmap = true,
+            "--seekable" => seekable = true,
+            "--block-tables" => block_tables = true,
+            "--block-size" => match iter.next().and_then(|n| n.parse().ok()) {
+                Some(n) => block_size = n,
+                None => error::exit(Error::Usage(String::from("--block-size needs a number of bytes"))),
+            },
+            "--range" => match iter.next().and_then(parse_range) {
+                Some(parsed) => range = Some(parsed),
+                None => error::exit(Error::Usage(String::from("--range needs START:LEN"))),
+            },
+            // "-" 是标准输入，其它以 - 开头的都是不认识的选项
+            _ if arg.starts_with('-') && arg != "-" => error::exit(Error::Usage(format!("Unknown option {}", arg))),
+            _ if input_file.is_some() => error::exit(Error::Usage(String::from("Only one input file can be given"))),
+            _ => input_file = Some(arg),
+        }
+    }
+
+    // 检查参数数量；用了 compress/decompress 时不给文件名就读标准输入
+    let input_file = match (input_file, mode) {
+        (Some(input_file), _) => input_file,
+        (None, Some(_)) => "-",
+        (None, None) => {
+            println!("Usage: {} [compress|decompress] [--rle] [--order1] [--words] [--preset english|code|json] [--table <table>]", args[0]);
+            println!("           [-o <output_file> | --output-dir <dir> | -c] [-k] [-f] [--mmap] [-n|--no-name] [-N|--name] [<input_file> | -]");
+            println!("       {} compress --seekable [--block-size N] [--block-tables] [<input_file> | -]", args[0]);
+            println!("       {} decompress --range START:LEN [-o <output_file>] [<input_file> | -]", args[0]);
+            println!("       {} stats [--bytes] [--json|--csv] <input_file>", args[0]);
+            println!("       {} codes [--bytes] [--json|--csv] <input_file>", args[0]);
+            println!("       {} tree [--format dot|svg] [--bytes] <input_file>", args[0]);
+            println!("       {} tree --trace [--format text|json|html] [--bytes] <input_file>", args[0]);
+            println!("       {} bench [--size N] [--iterations K] [file...]", args[0]);
+            println!("       {} bench --count [--size N] [--threads N] [file...]", args[0]);
+            println!("       {} fuzz <container|tree|payload> [--iterations N] [--seed S] [--corpus dir]", args[0]);
+            println!("       {} verify [--bless] <corpus_dir>", args[0]);
+            println!("       {} archive create|list|extract ...", args[0]);
+            println!("       {} train [-o <table>] <sample...>", args[0]);
+            println!("Exit status: 0 success, 1 usage, 2 I/O error, 3 not a compressed file, 4 corrupt data,");
+            println!("             5 unsupported version or feature, 6 limit exceeded, 130 interrupted");
+            process::exit(1);
+        }
+    };
+    if let Err(e) = options.check() {
+        error::exit(e);
+    }
+    if seekable && (options.rle || options.order1 || options.words || options.preset.is_some() || options.table.is_some()) {
+        error::exit(Error::Usage(String::from("--seekable cannot be combined with --rle, --order1, --words, --preset or --table")));
+    }
+    // 只取一段时不替换输入文件，默认写到标准输出
+    if range.is_some() && output_file.is_none() {
+        to_stdout = true;
+    }
+    if [output_file.is_some(), output_dir.is_some(), to_stdout].iter().filter(|set| **set).count() > 1 {
+        error::exit(Error::Usage(String::from("Only one of -o, --output-dir and -c can be given")));
     }
 
-    let input_file = &args[1];
-    // let output_file = &args[2];
+    // 读取输入文件，"-" 表示标准输入
+    // --mmap 时把文件映射到内存，不复制到堆上
+    let contents = if input_file == "-" {
+        let mut contents = Vec::new();
+        if let Err(e) = io::stdin().read_to_end(&mut contents) {
+            error::exit(Error::Io(String::from("Error reading standard input"), e));
+        }
+        mmap::Input::Owned(contents)
+    } else {
+        mmap::read(Path::new(input_file), use_mmap)
+            .unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", input_file), e)))
+    };
+    let compressing = mode.unwrap_or(!is_compressed(&contents));
+    if compressing && range.is_some() {
+        error::exit(Error::Usage(String::from("--range can only be used when decompressing")));
+    }
+    // 压缩时记录文件名、权限和修改时间（--no-name 只记录权限），解压时读出来恢复
+    let mut metadata = None;
+    if compressing && input_file != "-" {
+        options.metadata = Some(Metadata::from_file(Path::new(input_file), !no_name)
+            .unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading metadata of {}", input_file), e))));
+    } else if compressing {
+        // 标准输入没有文件名和权限，只记录原始长度
+        options.metadata = Some(Metadata::default());
+    } else {
+        metadata = read_metadata(&contents).ok().flatten();
+    }
 
-    let mut file_name=String::new();
-    for ch in input_file.chars() {
-        if ch=='/' {
-            file_name.clear();
+    // 输出位置：-o 指定的文件；-c 或者输入来自标准输入时写到标准输出；
+    // 否则和 gzip 一样，压缩成 <文件名>.byoct，解压时去掉 .byoct 后缀（-N 用记录的原始文件名），
+    // 放在输入文件旁边或者 --output-dir 下，写完之后删除输入文件，除非加了 -k
+    let mut remove_input = false;
+    let output_path = if let Some(output_file) = output_file {
+        Some(PathBuf::from(output_file))
+    } else if to_stdout || (input_file == "-" && output_dir.is_none()) {
+        None
+    } else {
+        let input_path = Path::new(input_file);
+        let name = match input_path.file_name() {
+            Some(name) if input_file != "-" => name.to_string_lossy().into_owned(),
+            _ => String::from("stdin"),
+        };
+        let restored_name = metadata.as_ref().and_then(|metadata| metadata.safe_name()).filter(|_| restore_name);
+        let output_name = if compressing {
+            format!("{}.{}", name, SUFFIX)
+        } else if let Some(restored_name) = restored_name {
+            restored_name.to_string()
+        } else if input_file == "-" {
+            name
         } else {
-            file_name.push(ch);
+            match name.strip_suffix(&format!(".{}", SUFFIX)) {
+                Some(stem) if !stem.is_empty() => stem.to_string(),
+                _ => error::exit(Error::Usage(format!("{} does not end in .{}, use -o or -N to name the output", input_file, SUFFIX))),
+            }
+        };
+        let dir = match output_dir {
+            Some(output_dir) => {
+                if let Err(e) = fs::create_dir_all(output_dir) {
+                    error::exit(Error::Io(format!("Error creating {}", output_dir), e));
+                }
+                PathBuf::from(output_dir)
+            },
+            None => input_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        remove_input = input_file != "-" && !keep && range.is_none();
+        Some(dir.join(output_name))
+    };
+    if let Some(path) = &output_path {
+        if path.exists() && !force {
+            error::exit(Error::Usage(format!("{} already exists, use -f to overwrite", path.display())));
+        }
+    }
+
+    // 标准错误是终端时显示进度条
+    let mut progress = progress::terminal_bar();
+    progress.cancel = Some(progress::cancel_on_interrupt());
+    let output = match (compressing, range) {
+        (true, _) if seekable => seekable::compress(&contents, block_size, block_tables, &mut progress),
+        (true, _) => compress_with(&contents, &options, &mut progress),
+        (false, Some((start, len))) if seekable::is_seekable(&contents) => {
+            seekable::decompress_range(&contents, start, len, &mut progress)
+        },
+        // 普通格式没有索引，只能整个解码再截取
+        (false, Some((start, len))) => decompress_with(&contents, options.table.as_slice(), &mut progress).map(|output| {
+            let start = (start as usize).min(output.len());
+            let end = start.saturating_add(len as usize).min(output.len());
+            output[start..end].to_vec()
+        }),
+        (false, None) => decompress_with(&contents, options.table.as_slice(), &mut progress),
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            // 进度条停在行中间，先换行
+            if matches!(e, Error::Cancelled) {
+                eprintln!();
+            }
+            error::exit(e);
+        },
+    };
+    // 压缩时和 gzip 一样，输出用输入的权限，否则 0600 的文件压缩之后别人也能读
+    if compressing {
+        metadata = options.metadata.as_ref().map(|recorded| Metadata { mode: recorded.mode, ..Metadata::default() });
+    }
+    let written = match output_path {
+        Some(path) => write_atomic(&path, &output, metadata.as_ref())
+            .map_err(|e| Error::Io(format!("Error writing {}", path.display()), e)),
+        None => io::stdout().lock().write_all(&output).map_err(|e| Error::Io(String::from("Error writing standard output"), e)),
+    };
+    if let Err(e) = written {
+        error::exit(e);
+    }
+    if remove_input {
+        if let Err(e) = fs::remove_file(input_file) {
+            error::exit(Error::Io(format!("Error removing {}", input_file), e));
         }
     }
+}
 
-    let dir_name=String::from(&file_name[0..file_name.rfind('.').unwrap()]);
-    fs::create_dir_all(dir_name.clone()).expect("Error creating directory");
-
-    // 读取输入文件
-    let contents = std::fs::read_to_string(input_file).expect("Error reading input file");
-    if is_binary(&contents){
-        let loaded_tree = fs::read_to_string("huffman_tree.txt").expect("Error reading tree file");
-        let deserialized_root = HuffmanNode::deserialize(loaded_tree.as_bytes()).expect("Error deserializing tree");
-        let contents=decode(&contents,&deserialized_root);
-        let output_file = format!("{0}/{0}.decoded", dir_name.clone());
-        std::fs::write(output_file, contents).expect("Error writing output file");
-    }else{
-        // 构建 hashmap
-        let hashmap=build_hashmap(&contents);
+// 先写到同一目录下的临时文件，设置好权限和修改时间再改名，
+// 中途失败或者被打断都不会留下写了一半的输出。要设置权限时临时文件先只给自己读写，
+// 设置之前别人也读不到内容
+fn write_atomic(path: &Path, contents: &[u8], metadata: Option<&Metadata>) -> io::Result<()> {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, process::id()));
+    let private = metadata.is_some_and(|metadata| metadata.mode.is_some());
+    let result = (|| {
+        let mut file = create_file(&temp, private)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        if let Some(metadata) = metadata {
+            if let Err(e) = metadata.apply(&temp) {
+                eprintln!("byoct: cannot restore permissions and mtime of {}: {}", path.display(), e);
+            }
+        }
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(unix)]
+fn create_file(path: &Path, private: bool) -> io::Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut options = fs::File::options();
+    options.write(true).create(true).truncate(true);
+    if private {
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+#[cfg(not(unix))]
+fn create_file(path: &Path, _: bool) -> io::Result<fs::File> {
+    fs::File::create(path)
+}
+
+fn is_compressed(contents: &[u8]) -> bool {
+    contents.starts_with(MAGIC) || seekable::is_seekable(contents)
+}
+
+// "START:LEN"，都是字节数
+fn parse_range(range: &str) -> Option<(u64, u64)> {
+    let (start, len) = range.split_once(':')?;
+    Some((start.parse().ok()?, len.parse().ok()?))
+}
+
+// 文件格式: MAGIC | VERSION | flags | [元数据] | 树长度(u32) | 树 | 比特数(u64) | 数据
+// 使用 1 阶上下文时，"树" 这一段存放的是整个上下文模型；
+// 按词编码时，前面再加上字典长度(u32)和字典；使用预设表时只有 1 字节的预设编号，
+// 使用训练好的表时只有 8 字节的表哈希
+fn compress(contents: &[u8], options: &Options) -> Vec<u8> {
+    compress_with(contents, options, &mut Progress::default()).expect("Compression without a cancel token cannot fail")
+}
+
+// 和 compress 一样，过程中通过 progress 报告进度，被取消时返回 Err(Error::Cancelled)
+fn compress_with(input: &[u8], options: &Options, progress: &mut Progress) -> Result<Vec<u8>, Error> {
+    let original_len = input.len() as u64;
+    progress.report(Phase::Counting, 0, original_len)?;
+    let mut flags = 0;
+    // 不是 UTF-8 又只用 order-0 时直接按字节统计和编码，不把输入复制成 String；
+    // rle、按词、1 阶上下文和预设表、训练好的表都按文本处理，还是要复制一份
+    let text_only = options.rle || options.words || options.order1 || options.preset.is_some() || options.table.is_some();
+    let mut raw_bytes = None;
+    let latin1: String;
+    let mut contents = match std::str::from_utf8(input) {
+        Ok(text) => text,
+        Err(_) if text_only => {
+            flags |= FLAG_BYTES;
+            latin1 = input.iter().map(|&byte| char::from(byte)).collect();
+            &latin1
+        },
+        Err(_) => {
+            flags |= FLAG_BYTES;
+            raw_bytes = Some(input);
+            ""
+        },
+    };
+    let rle_contents;
+    if options.rle {
+        flags |= FLAG_RLE;
+        rle_contents = rle::encode(contents);
+        contents = &rle_contents;
+    }
+    let word_ids;
+    let mut dictionary = Vec::new();
+    if options.words {
+        flags |= FLAG_WORDS;
+        let (words, encoded) = words::encode(contents)?;
+        dictionary = words::serialize_dictionary(&words);
+        word_ids = encoded;
+        contents = &word_ids;
+    }
+
+    let mut tree = Vec::new();
+    // order-0 直接把码写进打包好的字节；预设表、训练好的表和 1 阶上下文先得到 "0101..." 再打包
+    let mut data = Vec::new();
+    let mut bit_len = 0;
+    if options.order1 {
+        flags |= FLAG_ORDER1;
+    }
+    if options.preset.is_some() {
+        flags |= FLAG_PRESET;
+    }
+    if options.table.is_some() {
+        flags |= FLAG_TABLE;
+    }
+    // 预设表、训练好的表和 1 阶上下文只在阶段开始和结束时报告，order-0 按块报告
+    let total = raw_bytes.map_or(contents.len(), <[u8]>::len) as u64;
+    if let Some(input) = raw_bytes {
+        let threads = count::threads();
+        let mut counts = count::Frequencies::default();
+        let mut done = 0;
+        for group in input.chunks(count::PART_SIZE * threads) {
+            counts.merge(count::count_bytes(group, threads));
+            done += group.len() as u64;
+            progress.report(Phase::Counting, done, total)?;
+        }
+        // 按字节编码时用 no_std 的核心建树和编码，和可随机访问的格式一样
+        progress.report(Phase::Building, 0, total)?;
+        let codes = huffman::Codes::from_counts(counts.bytes())?;
+        bit_len = counts.bytes().iter().enumerate()
+            .map(|(byte, count)| count * codes.code(byte as u8).map_or(0, |(_, len)| len as usize))
+            .sum();
+        data = vec![0; bit_len.div_ceil(8)];
+        let mut writer = huffman::BitWriter::new(&mut data);
+        let mut done = 0;
+        for chunk in input.chunks(progress::CHUNK_SIZE) {
+            for &byte in chunk {
+                let (code, len) = codes.code(byte).ok_or(Error::Corrupt("Invalid code"))?;
+                writer.write(code, len)?;
+            }
+            done += chunk.len() as u64;
+            progress.report(Phase::Encoding, done, total)?;
+        }
+        tree = serialize_tree(&HuffmanNode::from_codes(&codes, codes.root()));
+    } else if let (false, Some(id)) = (contents.is_empty(), options.preset) {
+        progress.report(Phase::Building, 0, total)?;
+        let root = presets::build_tree(id)?;
+        progress.report(Phase::Encoding, 0, total)?;
+        (data, bit_len) = pack_bits(&presets::encode(contents, &root));
+        progress.report(Phase::Encoding, total, total)?;
+        tree = vec![id];
+    } else if let (false, Some(table)) = (contents.is_empty(), &options.table) {
+        progress.report(Phase::Encoding, 0, total)?;
+        (data, bit_len) = pack_bits(&presets::encode(contents, &table.root));
+        progress.report(Phase::Encoding, total, total)?;
+        tree = table.hash.to_le_bytes().to_vec();
+    } else if !contents.is_empty() && options.order1 {
+        progress.report(Phase::Building, 0, total)?;
+        let model = context::build_model(contents);
+        progress.report(Phase::Encoding, 0, total)?;
+        (data, bit_len) = pack_bits(&context::encode(contents, &model));
+        progress.report(Phase::Encoding, total, total)?;
+        tree = context::serialize_model(&model);
+    } else if !contents.is_empty() {
+        // 构建 hashmap，每组输入分给所有的核一起统计
+        let threads = count::threads();
+        let mut counts = count::Frequencies::default();
+        let mut done = 0;
+        for group in progress::chunks_of(contents, count::PART_SIZE * threads) {
+            counts.merge(count::count_text(group, threads));
+            done += group.len() as u64;
+            progress.report(Phase::Counting, done, total)?;
+        }
+        let hashmap = counts.into_hashmap();
         // 构建 Huffman 树
+        progress.report(Phase::Building, 0, total)?;
         let frequencies = build_huffman_tree(&hashmap);
         // 构建 Huffman 编码
-        let codes = build_huffman_codes(&frequencies);
-        // 编码并写入输出文件
-        let encoded_contents = encode_contents(&contents, &codes);
+        let codes = build_bit_codes(&frequencies);
+        // 编码，先按频率算出总比特数，直接写进输出的字节
+        bit_len = encoded_len(&hashmap, &codes);
+        data = vec![0; bit_len.div_ceil(8)];
+        let mut writer = huffman::BitWriter::new(&mut data);
+        let mut done = 0;
+        for chunk in progress::chunks(contents) {
+            for ch in chunk.chars() {
+                let (code, len) = codes[&ch];
+                writer.write(code, len)?;
+            }
+            done += chunk.len() as u64;
+            progress.report(Phase::Encoding, done, total)?;
+        }
+        tree = serialize_tree(&frequencies);
+    }
+    if !contents.is_empty() && options.words {
+        let mut section = (dictionary.len() as u32).to_le_bytes().to_vec();
+        section.extend_from_slice(&dictionary);
+        section.extend_from_slice(&tree);
+        tree = section;
+    }
 
-        let output_file = format!("{0}/{0}.huffman", dir_name.clone());
-        std::fs::write(output_file, encoded_contents).expect("Error writing output file");
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    if let Some(metadata) = &options.metadata {
+        out.push(flags | FLAG_METADATA);
+        // 原始长度在这里填上，解压时按它预先分配输出
+        Metadata { size: Some(original_len), ..metadata.clone() }.serialize(&mut out);
+    } else {
+        out.push(flags);
+    }
+    out.extend_from_slice(&(tree.len() as u32).to_le_bytes());
+    out.extend_from_slice(&tree);
+    out.extend_from_slice(&(bit_len as u64).to_le_bytes());
+    progress.report(Phase::Writing, 0, original_len)?;
+    out.extend_from_slice(&data);
+    progress.report(Phase::Writing, original_len, original_len)?;
+    Ok(out)
+}
 
-        let serialized=serialize_tree(&frequencies);
-        fs::write(dir_name.clone()+".huffman.tree",serialized).expect("Error writing tree file")
+// 读取 MAGIC、版本、flags 和元数据，返回 (flags, 元数据, 树长度字段的位置)
+fn read_header(bytes: &[u8]) -> Result<(u8, Option<Metadata>, usize), Error> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(Error::Format("Not a compressed file"));
     }
+    let mut pos = MAGIC.len();
+    if bytes.len() < pos + 2 {
+        return Err(Error::Corrupt("Truncated header"));
+    }
+    if bytes[pos] != VERSION {
+        return Err(Error::UnsupportedVersion(bytes[pos]));
+    }
+    let flags = bytes[pos + 1];
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(Error::UnsupportedFeature("Unknown flags"));
+    }
+    pos += 2;
+    let mut metadata = None;
+    if flags & FLAG_METADATA != 0 {
+        metadata = Some(Metadata::deserialize(bytes, &mut pos)?);
+    }
+    Ok((flags, metadata, pos))
 }
 
-fn is_binary(contents: &str) -> bool {
-    for ch in contents.chars() {
-        if ch!= '0' && ch!= '1' {
-            return false;
+fn read_metadata(bytes: &[u8]) -> Result<Option<Metadata>, Error> {
+    read_header(bytes).map(|(_, metadata, _)| metadata)
+}
+
+fn decompress(bytes: &[u8], tables: &[Table]) -> Result<Vec<u8>, Error> {
+    decompress_with(bytes, tables, &mut Progress::default())
+}
+
+// 用 --table 压缩的文件要在 tables 里找到哈希相同的表；进度按压缩数据的字节数报告
+fn decompress_with(bytes: &[u8], tables: &[Table], progress: &mut Progress) -> Result<Vec<u8>, Error> {
+    if seekable::is_seekable(bytes) {
+        return seekable::decompress(bytes, progress);
+    }
+    let total = bytes.len() as u64;
+    progress.report(Phase::Building, 0, total)?;
+    let (flags, metadata, mut pos) = read_header(bytes)?;
+    let original_len = metadata.and_then(|metadata| metadata.size);
+    if bytes.len() < pos + 4 {
+        return Err(Error::Corrupt("Truncated header"));
+    }
+    let tree_len = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+    pos += 4;
+    if bytes.len() < pos + tree_len + 8 {
+        return Err(Error::Corrupt("Truncated tree"));
+    }
+    let mut tree = &bytes[pos..pos + tree_len];
+    pos += tree_len;
+    let bit_len = u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap()) as usize;
+    pos += 8;
+
+    let mut contents = String::new();
+    // order-0 按字节编码、没有 rle 和按词时直接解成字节，不经过 String
+    let mut raw_output = None;
+    if tree_len > 0 {
+        let mut dictionary = Vec::new();
+        if flags & FLAG_WORDS != 0 {
+            let len = u32::from_le_bytes(tree.get(..4).ok_or(Error::Corrupt("Truncated dictionary"))?.try_into().unwrap()) as usize;
+            let section = tree.get(4..4 + len).ok_or(Error::Corrupt("Truncated dictionary"))?;
+            dictionary = words::deserialize_dictionary(section)?;
+            tree = &tree[4 + len..];
+        }
+        let data = &bytes[pos..];
+        if data.len() * 8 < bit_len {
+            return Err(Error::Corrupt("Truncated data"));
+        }
+        // 预设表、训练好的表和 1 阶上下文的解码器读 "0101..."，order-0 直接读打包好的字节
+        progress.report(Phase::Decoding, 0, total)?;
+        if flags & FLAG_PRESET != 0 {
+            let root = presets::build_tree(*tree.first().ok_or(Error::Corrupt("Missing preset"))?)?;
+            contents = presets::decode(&unpack_bits(data, bit_len)?, &root)?;
+        } else if flags & FLAG_TABLE != 0 {
+            let hash = u64::from_le_bytes(tree.try_into().map_err(|_| Error::Corrupt("Invalid table hash"))?);
+            let table = tables.iter().find(|table| table.hash == hash).ok_or(Error::MissingTable(hash))?;
+            contents = presets::decode(&unpack_bits(data, bit_len)?, &table.root)?;
+        } else if flags & FLAG_ORDER1 != 0 {
+            let model = context::deserialize_model(tree)?;
+            contents = context::decode(&unpack_bits(data, bit_len)?, &model)?;
+        } else {
+            let root = HuffmanNode::deserialize(tree)?;
+            // 按头部记录的原始长度预先分配，每个比特最多解出一个字符，
+            // 损坏的长度也不会让这里分配过多的内存
+            let capacity = original_len.unwrap_or(0).min(bit_len as u64 * 4) as usize;
+            if flags & (FLAG_BYTES | FLAG_RLE | FLAG_WORDS) == FLAG_BYTES {
+                // 按字节编码的用 no_std 的核心解码，核心不报告进度，只在开始和结束时报告。
+                // 每个字节至少占一个比特，前面检查过 bit_len 不超过数据的比特数
+                let codes = root.to_codes()?;
+                let mut output = vec![0; original_len.map_or(bit_len, |len| (len as usize).min(bit_len))];
+                let written = huffman::decode_into(data, bit_len, &codes, &mut output)?;
+                output.truncate(written);
+                raw_output = Some(output);
+            } else {
+                contents = String::with_capacity(capacity);
+                decode_into(data, bit_len, &root, progress, |ch| {
+                    contents.push(ch);
+                    Ok(())
+                })?;
+            }
+        }
+        if flags & FLAG_WORDS != 0 {
+            contents = words::decode(&contents, &dictionary)?;
         }
     }
-    true
+
+    if flags & FLAG_RLE != 0 {
+        contents = rle::decode(&contents)?;
+    }
+    let output = if let Some(output) = raw_output {
+        output
+    } else if flags & FLAG_BYTES != 0 {
+        contents.chars().map(|ch| u8::try_from(ch).map_err(|_| Error::Corrupt("Invalid byte"))).collect::<Result<Vec<u8>, _>>()?
+    } else {
+        contents.into_bytes()
+    };
+    if original_len.is_some_and(|len| len != output.len() as u64) {
+        return Err(Error::Corrupt("Decoded length does not match header"));
+    }
+    progress.report(Phase::Writing, total, total)?;
+    Ok(output)
 }
 
-fn build_hashmap(contents: &str) -> HashMap<char, usize> {
-    let mut frequencies = HashMap::new();
-    for ch in contents.chars() {
-        *frequencies.entry(ch).or_insert(0) += 1;
+// 把 "0101..." 打包成字节，高位在前，返回 (字节, 比特数)
+fn pack_bits(bits: &str) -> (Vec<u8>, usize) {
+    let mut out = vec![0u8; bits.len().div_ceil(8)];
+    for (i, bit) in bits.bytes().enumerate() {
+        if bit == b'1' {
+            out[i / 8] |= 0x80 >> (i % 8);
+        }
     }
-    // for (ch, freq) in frequencies.iter_mut() {
-    //     println!("char: {}, freq: {}", ch, freq)
-    // }
-    frequencies
+    (out, bits.len())
 }
 
+fn unpack_bits(bytes: &[u8], bit_len: usize) -> Result<String, Error> {
+    if bytes.len() * 8 < bit_len {
+        return Err(Error::Corrupt("Truncated data"));
+    }
+    let mut bits = String::with_capacity(bit_len);
+    for i in 0..bit_len {
+        bits.push(if bytes[i / 8] & (0x80 >> (i % 8)) != 0 { '1' } else { '0' });
+    }
+    Ok(bits)
+}
+
+// 大输入用多个线程统计，见 count.rs
+fn build_hashmap(contents: &str) -> HashMap<char, usize> {
+    count::count_text(contents, count::threads()).into_hashmap()
+}
+
+// 和 build_huffman_tree_with 得到同一棵树，但不用每次合并都重新排序：叶子排好序放在一个队列里，
+// 新父节点的频率不会变小，按生成顺序放在另一个队列里。键相同时父节点（ch 是 None）在叶子前面，
+// 父节点之间先生成的在前，和稳定排序的顺序一样
 fn build_huffman_tree(frequencies: &HashMap<char, usize>) -> Box<HuffmanNode> {
+    let mut leaves = frequencies.iter().map(|(ch, freq)| HuffmanNode::new(*ch, *freq)).collect::<Vec<_>>();
+    leaves.sort_by_key(|node| (node.freq, node.ch));
+    let mut leaves = VecDeque::from(leaves);
+    let mut parents: VecDeque<HuffmanNode> = VecDeque::new();
+    let pop = |leaves: &mut VecDeque<HuffmanNode>, parents: &mut VecDeque<HuffmanNode>| match (leaves.front(), parents.front()) {
+        (Some(leaf), Some(parent)) if leaf.freq < parent.freq => leaves.pop_front(),
+        (_, Some(_)) => parents.pop_front(),
+        _ => leaves.pop_front(),
+    };
+
+    while leaves.len() + parents.len() > 1 {
+        let left = pop(&mut leaves, &mut parents).unwrap();
+        let right = pop(&mut leaves, &mut parents).unwrap();
+        parents.push_back(HuffmanNode {
+            ch: None,
+            freq: left.freq + right.freq,
+            left: Some(Box::new(left)),
+            right: Some(Box::new(right)),
+        });
+    }
+
+    Box::new(parents.pop_front().or_else(|| leaves.pop_front()).unwrap())
+}
+
+// 每次合并之后调用 on_merge(队列, 新父节点在队列里的下标)，--trace 用它记录建树的过程
+fn build_huffman_tree_with(frequencies: &HashMap<char, usize>, mut on_merge: impl FnMut(&[HuffmanNode], usize)) -> Box<HuffmanNode> {
     let mut nodes = frequencies.into_iter().map(|(ch, freq)| HuffmanNode::new(*ch, *freq)).collect::<Vec<_>>();
-    nodes.sort_by_key(|node| node.freq);
+    // 频率相同时按字符排序，同样的频率总是得到同样的树
+    nodes.sort_by_key(|node| (node.freq, node.ch));
 
     while nodes.len() > 1 {
         let left = nodes.remove(0);
         let right = nodes.remove(0);
         let parent = HuffmanNode {
-            ch: Some('\0'),
+            ch: None,
             freq: left.freq + right.freq,
             left: Some(Box::new(left)),
             right: Some(Box::new(right)),
         };
+        let key = (parent.freq, parent.ch);
         nodes.push(*Box::new(parent));
-        nodes.sort_by_key(|node| node.freq);
+        nodes.sort_by_key(|node| (node.freq, node.ch));
+        // 排序是稳定的，新节点排在所有相同键的节点之后
+        on_merge(&nodes, nodes.partition_point(|node| (node.freq, node.ch) <= key) - 1);
     }
 
     Box::new(nodes.into_iter().next().unwrap())
@@ -231,15 +925,14 @@ fn build_huffman_codes(root: &Box<HuffmanNode>) -> HashMap<char, String> {
     let mut codes = HashMap::new();
     let mut current_code = String::new();
     build_huffman_codes_recursive(root, &mut codes, &mut current_code);
-    for (ch, code) in codes.iter_mut() {
-        println!("ch: {}, code: {}",ch,code);
-    }
     codes
 }
 
 fn build_huffman_codes_recursive(node: &HuffmanNode, codes: &mut HashMap<char, String>, current_code: &mut String) {
-    if node.ch!= Some('\0')  {
-        codes.insert(node.ch.expect("Invalid node"), current_code.clone());
+    if let Some(ch) = node.ch {
+        // 只有一种字符时根节点就是叶子，也要给它一个比特
+        let code = if current_code.is_empty() { String::from("0") } else { current_code.clone() };
+        codes.insert(ch, code);
         return;
     }
 
@@ -256,6 +949,18 @@ fn build_huffman_codes_recursive(node: &HuffmanNode, codes: &mut HashMap<char, S
     current_code.pop();
 }
 
+// 每个字符的码，和 huffman::Codes 一样是 (码的低 len 位, len)，可以直接交给 BitWriter
+fn build_bit_codes(root: &HuffmanNode) -> HashMap<char, (u128, u8)> {
+    let mut codes = HashMap::new();
+    build_huffman_codes_recursive(root, &mut codes, &mut String::new());
+    codes.into_iter().map(|(ch, code)| (ch, (u128::from_str_radix(&code, 2).unwrap(), code.len() as u8))).collect()
+}
+
+// 按频率和码长算出编码之后的总比特数
+fn encoded_len(frequencies: &HashMap<char, usize>, codes: &HashMap<char, (u128, u8)>) -> usize {
+    frequencies.iter().map(|(ch, freq)| freq * codes[ch].1 as usize).sum()
+}
+
 fn encode_contents(contents: &str, codes: &HashMap<char, String>) -> String {
     let mut encoded_contents = String::new();
     for ch in contents.chars() {
@@ -264,21 +969,44 @@ fn encode_contents(contents: &str, codes: &HashMap<char, String>) -> String {
     encoded_contents
 }
 
-fn decode(encoded_text: &str, root: &HuffmanNode) -> String {
-    let mut node = root;
+fn decode(encoded_text: &str, root: &HuffmanNode) -> Result<String, Error> {
+    if encoded_text.bytes().any(|bit| bit != b'0' && bit != b'1') {
+        return Err(Error::Corrupt("Invalid bit"));
+    }
+    let (data, bit_len) = pack_bits(encoded_text);
     let mut decoded_text = String::new();
-    for bit in encoded_text.chars() {
+    decode_into(&data, bit_len, root, &mut Progress::default(), |ch| {
+        decoded_text.push(ch);
+        Ok(())
+    })?;
+    Ok(decoded_text)
+}
+
+// 解码打包好的前 bit_len 个比特，每解出一个字符交给 emit，由调用者决定放进 String 还是字节
+fn decode_into(data: &[u8], bit_len: usize, root: &HuffmanNode, progress: &mut Progress, mut emit: impl FnMut(char) -> Result<(), Error>) -> Result<(), Error> {
+    let mut reader = huffman::BitReader::new(data, bit_len)?;
+    let mut node = root;
+    let total = bit_len as u64 / 8;
+    let mut i = 0;
+    while let Some(bit) = reader.read() {
+        if i % (progress::CHUNK_SIZE * 8) == 0 {
+            progress.report(Phase::Decoding, i as u64 / 8, total)?;
+        }
+        i += 1;
+        if let Some(ch) = root.ch {
+            emit(ch)?;
+            continue;
+        }
         node = match bit {
-            '0' => node.left.as_ref().unwrap(),
-            '1' => node.right.as_ref().unwrap(),
-            _ => panic!("Invalid bit"),
+            0 => node.left.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
+            _ => node.right.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
         };
-        if node.ch.is_some() {
-            decoded_text.push(node.ch.unwrap());
+        if let Some(ch) = node.ch {
+            emit(ch)?;
             node = root;
         }
     }
-    decoded_text
+    Ok(())
 }
 
 
@@ -0,0 +1,264 @@
+// C 接口，给 C/C++ 的服务直接调用压缩器。压缩用默认选项（和不带选项的 byoct compress 一样），
+// 解压认所有格式，包括可随机访问的格式；用 --table 压缩的数据这里解不了。
+// 流式的句柄并不流式：容器格式要看完整个输入才能建树，编码器和解码器都把写进来的数据全部攒在内存里，
+// finish 时一次处理完，之后才能读输出。解码器也不能边收边解，内存至少是压缩数据加上解压后的数据。
+// 函数里的 panic 都被拦下来，返回 BYOCT_ERROR_INTERNAL，不会展开到 C 代码里。
+//
+// libbyoct.rs 把这些函数编译成 cdylib/staticlib。byoct.h 由 test/capi.rs 从这个文件生成：
+// 第一段说明、BYOCT_ 常量、句柄类型和 byoct_ 函数连同紧挨在前面的注释放进头文件，签名按 C_TYPES 翻译。
+
+use std::ffi::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use super::{compress, decompress, Error, Options};
+
+// 成功
+pub const BYOCT_OK: i32 = 0;
+// 参数不对：空指针，或者流式句柄的调用顺序不对
+pub const BYOCT_ERROR_ARGUMENT: i32 = 1;
+// 输入不是压缩数据
+pub const BYOCT_ERROR_FORMAT: i32 = 2;
+// 压缩数据损坏
+pub const BYOCT_ERROR_CORRUPT: i32 = 3;
+// 格式版本或功能不支持，通常是更新的版本写的数据
+pub const BYOCT_ERROR_UNSUPPORTED: i32 = 4;
+// 超出实现的限制，比如树太深
+pub const BYOCT_ERROR_LIMIT: i32 = 5;
+// 数据是用共享编码表（byoct compress --table）压缩的
+pub const BYOCT_ERROR_MISSING_TABLE: i32 = 6;
+// 输出缓冲区不够，*dst_len 是需要的长度
+pub const BYOCT_ERROR_BUFFER_TOO_SMALL: i32 = 7;
+// 内部错误，不应该出现
+pub const BYOCT_ERROR_INTERNAL: i32 = 8;
+
+fn code(e: &Error) -> i32 {
+    match e {
+        Error::Usage(_) => BYOCT_ERROR_ARGUMENT,
+        Error::Format(_) => BYOCT_ERROR_FORMAT,
+        Error::Corrupt(_) => BYOCT_ERROR_CORRUPT,
+        Error::UnsupportedVersion(_) | Error::UnsupportedFeature(_) => BYOCT_ERROR_UNSUPPORTED,
+        Error::Limit(_) => BYOCT_ERROR_LIMIT,
+        Error::MissingTable(_) => BYOCT_ERROR_MISSING_TABLE,
+        Error::Io(..) | Error::Cancelled => BYOCT_ERROR_INTERNAL,
+    }
+}
+
+// 跑 f，panic 时返回 BYOCT_ERROR_INTERNAL
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(BYOCT_ERROR_INTERNAL)
+}
+
+// 长度为 0 时允许空指针
+unsafe fn input<'a>(src: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (src.is_null(), len) {
+        (true, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(src, len)),
+    }
+}
+
+// 放得下时把 data 复制到 dst，*dst_len 总是 data 的长度
+unsafe fn output(data: &[u8], dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+    if dst_len.is_null() || (dst.is_null() && dst_cap > 0) {
+        return BYOCT_ERROR_ARGUMENT;
+    }
+    *dst_len = data.len();
+    if data.len() > dst_cap {
+        return BYOCT_ERROR_BUFFER_TOO_SMALL;
+    }
+    if !data.is_empty() {
+        slice::from_raw_parts_mut(dst, data.len()).copy_from_slice(data);
+    }
+    BYOCT_OK
+}
+
+// 压缩 src_len 字节最多输出多少字节。
+// 数据部分不超过输入：Huffman 码不比把 UTF-8（或者按字节编码时的 8 位定长码）当作前缀码更长。
+// 树每个叶子最多 38 字节加频率的位数，每个内部节点最多 26 字节加频率的位数，叶子不超过输入的字节数
+#[no_mangle]
+pub extern "C" fn byoct_compress_bound(src_len: usize) -> usize {
+    // MAGIC、版本、flags、树长度、比特数
+    const HEADER: usize = 4 + 1 + 1 + 4 + 8;
+    let digits = src_len.max(1).ilog10() as usize + 1;
+    let leaves = src_len.min(char::MAX as usize + 1);
+    let tree = leaves.saturating_mul(64 + 2 * digits);
+    HEADER.saturating_add(tree).saturating_add(src_len)
+}
+
+// 用默认选项压缩 src 的 src_len 字节，写到 dst 里，*dst_len 是压缩后的长度。
+// dst_cap 至少是 byoct_compress_bound(src_len) 时一定放得下
+#[no_mangle]
+pub unsafe extern "C" fn byoct_compress(src: *const u8, src_len: usize, dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+    guard(|| match input(src, src_len) {
+        Some(src) => output(&compress(src, &Options::default()), dst, dst_cap, dst_len),
+        None => BYOCT_ERROR_ARGUMENT,
+    })
+}
+
+// 解压 src 的 src_len 字节，写到 dst 里，*dst_len 是解压后的长度。
+// 放不下时返回 BYOCT_ERROR_BUFFER_TOO_SMALL，*dst_len 是需要的长度
+#[no_mangle]
+pub unsafe extern "C" fn byoct_decompress(src: *const u8, src_len: usize, dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+    guard(|| match input(src, src_len).map(|src| decompress(src, &[])) {
+        Some(Ok(data)) => output(&data, dst, dst_cap, dst_len),
+        Some(Err(e)) => code(&e),
+        None => BYOCT_ERROR_ARGUMENT,
+    })
+}
+
+// 错误码的说明，返回的字符串是静态的，不用释放
+#[no_mangle]
+pub extern "C" fn byoct_error_string(code: i32) -> *const c_char {
+    let message = match code {
+        BYOCT_OK => c"Success",
+        BYOCT_ERROR_ARGUMENT => c"Invalid argument",
+        BYOCT_ERROR_FORMAT => c"Not a compressed file",
+        BYOCT_ERROR_CORRUPT => c"Corrupt data",
+        BYOCT_ERROR_UNSUPPORTED => c"Unsupported format version or feature",
+        BYOCT_ERROR_LIMIT => c"Limit exceeded",
+        BYOCT_ERROR_MISSING_TABLE => c"Compressed with a shared code table",
+        BYOCT_ERROR_BUFFER_TOO_SMALL => c"Output buffer too small",
+        BYOCT_ERROR_INTERNAL => c"Internal error",
+        _ => c"Unknown error",
+    };
+    message.as_ptr()
+}
+
+// 编码器和解码器共用：攒输入，finish 时一次处理完，之后分次读出
+#[derive(Default)]
+struct Stream {
+    input: Vec<u8>,
+    output: Option<Vec<u8>>,
+    read: usize,
+}
+
+impl Stream {
+    unsafe fn write(&mut self, src: *const u8, len: usize) -> i32 {
+        match (&self.output, input(src, len)) {
+            (None, Some(src)) => {
+                self.input.extend_from_slice(src);
+                BYOCT_OK
+            },
+            _ => BYOCT_ERROR_ARGUMENT,
+        }
+    }
+
+    fn finish(&mut self, process: impl FnOnce(&[u8]) -> Result<Vec<u8>, Error>) -> i32 {
+        if self.output.is_some() {
+            return BYOCT_ERROR_ARGUMENT;
+        }
+        match process(&self.input) {
+            Ok(output) => {
+                self.input = Vec::new();
+                self.output = Some(output);
+                BYOCT_OK
+            },
+            Err(e) => code(&e),
+        }
+    }
+
+    unsafe fn read(&mut self, dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+        let Some(output) = &self.output else {
+            return BYOCT_ERROR_ARGUMENT;
+        };
+        if dst_len.is_null() || (dst.is_null() && dst_cap > 0) {
+            return BYOCT_ERROR_ARGUMENT;
+        }
+        let n = dst_cap.min(output.len() - self.read);
+        if n > 0 {
+            slice::from_raw_parts_mut(dst, n).copy_from_slice(&output[self.read..self.read + n]);
+        }
+        self.read += n;
+        *dst_len = n;
+        BYOCT_OK
+    }
+}
+
+// 流式编码器，不透明的句柄。写进来的数据全部留在内存里，finish 之前没有任何输出
+pub struct Encoder(Stream);
+
+// 流式解码器，不透明的句柄。写进来的压缩数据全部留在内存里，finish 之前不解码，不能边收边解
+pub struct Decoder(Stream);
+
+// 新建编码器，用完要 byoct_encoder_free
+#[no_mangle]
+pub extern "C" fn byoct_encoder_new() -> *mut Encoder {
+    Box::into_raw(Box::new(Encoder(Stream::default())))
+}
+
+// 追加要压缩的数据，finish 之后不能再写
+#[no_mangle]
+pub unsafe extern "C" fn byoct_encoder_write(encoder: *mut Encoder, src: *const u8, src_len: usize) -> i32 {
+    match encoder.as_mut() {
+        Some(encoder) => guard(|| encoder.0.write(src, src_len)),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 压缩写进来的所有数据，之后可以读输出
+#[no_mangle]
+pub unsafe extern "C" fn byoct_encoder_finish(encoder: *mut Encoder) -> i32 {
+    match encoder.as_mut() {
+        Some(encoder) => guard(|| encoder.0.finish(|input| Ok(compress(input, &Options::default())))),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 读最多 dst_cap 字节压缩后的数据，*dst_len 是读到的长度，读完之后是 0
+#[no_mangle]
+pub unsafe extern "C" fn byoct_encoder_read(encoder: *mut Encoder, dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+    match encoder.as_mut() {
+        Some(encoder) => guard(|| encoder.0.read(dst, dst_cap, dst_len)),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 释放编码器，空指针什么也不做
+#[no_mangle]
+pub unsafe extern "C" fn byoct_encoder_free(encoder: *mut Encoder) {
+    if !encoder.is_null() {
+        drop(Box::from_raw(encoder));
+    }
+}
+
+// 新建解码器，用完要 byoct_decoder_free
+#[no_mangle]
+pub extern "C" fn byoct_decoder_new() -> *mut Decoder {
+    Box::into_raw(Box::new(Decoder(Stream::default())))
+}
+
+// 追加压缩数据，finish 之后不能再写
+#[no_mangle]
+pub unsafe extern "C" fn byoct_decoder_write(decoder: *mut Decoder, src: *const u8, src_len: usize) -> i32 {
+    match decoder.as_mut() {
+        Some(decoder) => guard(|| decoder.0.write(src, src_len)),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 解压写进来的所有数据，之后可以读输出。出错之后可以接着写，再 finish 一次
+#[no_mangle]
+pub unsafe extern "C" fn byoct_decoder_finish(decoder: *mut Decoder) -> i32 {
+    match decoder.as_mut() {
+        Some(decoder) => guard(|| decoder.0.finish(|input| decompress(input, &[]))),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 读最多 dst_cap 字节解压后的数据，*dst_len 是读到的长度，读完之后是 0
+#[no_mangle]
+pub unsafe extern "C" fn byoct_decoder_read(decoder: *mut Decoder, dst: *mut u8, dst_cap: usize, dst_len: *mut usize) -> i32 {
+    match decoder.as_mut() {
+        Some(decoder) => guard(|| decoder.0.read(dst, dst_cap, dst_len)),
+        None => BYOCT_ERROR_ARGUMENT,
+    }
+}
+
+// 释放解码器，空指针什么也不做
+#[no_mangle]
+pub unsafe extern "C" fn byoct_decoder_free(decoder: *mut Decoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
@@ -0,0 +1,77 @@
+// byoct codes <file> [--bytes] [--json|--csv]
+// 输出文件的 Huffman 编码表：符号、频率、编码、码长以及这个符号一共占多少比特，按频率排序。
+
+use std::fs;
+
+use super::stats::{escape_json, escape_symbol};
+use super::{build_hashmap, build_huffman_codes, build_huffman_tree, error, Error};
+
+pub fn run(args: &[String]) {
+    let mut bytes_mode = false;
+    let mut format = "text";
+    let mut input_file = None;
+    for arg in args {
+        match arg.as_str() {
+            "--bytes" => bytes_mode = true,
+            "--json" => format = "json",
+            "--csv" => format = "csv",
+            _ => input_file = Some(arg),
+        }
+    }
+    let input_file = match input_file {
+        Some(input_file) => input_file,
+        None => {
+            println!("Usage: byoct codes [--bytes] [--json|--csv] <input_file>");
+            std::process::exit(1);
+        }
+    };
+
+    let contents = fs::read(input_file)
+        .unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", input_file), e)));
+    // 和 stats 一样，按字节统计时每个字节当作码点 0..=255 的字符
+    let text = match std::str::from_utf8(&contents) {
+        Ok(text) if !bytes_mode => text.to_string(),
+        _ => {
+            bytes_mode = true;
+            contents.iter().map(|&byte| char::from(byte)).collect()
+        },
+    };
+
+    let hashmap = build_hashmap(&text);
+    let mut table: Vec<(char, usize, String)> = Vec::new();
+    if !hashmap.is_empty() {
+        let root = build_huffman_tree(&hashmap);
+        let codes = build_huffman_codes(&root);
+        table = hashmap.iter().map(|(ch, freq)| (*ch, *freq, codes[ch].clone())).collect();
+    }
+    // 频率相同时短码在前，再按符号排序，保证输出顺序固定
+    table.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.len().cmp(&b.2.len())).then(a.0.cmp(&b.0)));
+
+    let raw_symbol = |ch: char| if bytes_mode { format!("0x{:02x}", ch as u32) } else { ch.to_string() };
+    match format {
+        "json" => {
+            println!("[");
+            for (i, (ch, freq, code)) in table.iter().enumerate() {
+                let comma = if i + 1 < table.len() { "," } else { "" };
+                println!("  {{\"symbol\": \"{}\", \"count\": {}, \"code\": \"{}\", \"length\": {}, \"bits\": {}}}{}",
+                    escape_json(&raw_symbol(*ch)), freq, code, code.len(), freq * code.len(), comma);
+            }
+            println!("]");
+        },
+        "csv" => {
+            println!("symbol,count,code,length,bits");
+            for (ch, freq, code) in table.iter() {
+                println!("\"{}\",{},{},{},{}", raw_symbol(*ch).replace('"', "\"\""), freq, code, code.len(), freq * code.len());
+            }
+        },
+        _ => {
+            let total_bits: usize = table.iter().map(|(_, freq, code)| freq * code.len()).sum();
+            println!("{:>8}  {:>10}  {:<24}  {:>6}  {:>10}", "symbol", "count", "code", "length", "bits");
+            for (ch, freq, code) in table.iter() {
+                println!("{:>8}  {:>10}  {:<24}  {:>6}  {:>10}", escape_symbol(*ch, bytes_mode), freq, code, code.len(), freq * code.len());
+            }
+            println!();
+            println!("Total: {} bits ({} bytes) for {} symbols", total_bits, total_bits.div_ceil(8), table.len());
+        },
+    }
+}
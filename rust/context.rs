@@ -0,0 +1,154 @@
+// 1 阶上下文模型：按前一个字符分别统计频率，每个上下文用自己的 Huffman 表。
+// 出现次数少的上下文单独建表反而更亏（表本身要写进文件头），
+// 这些上下文合并到一张共享的 0 阶表里。
+
+use std::collections::HashMap;
+
+use super::{build_hashmap, build_huffman_codes_recursive, build_huffman_tree, serialize_tree, Error, HuffmanNode};
+
+pub struct ContextModel {
+    // 第一个字符和被合并的上下文使用的共享表
+    fallback: Box<HuffmanNode>,
+    tables: Vec<HuffmanNode>,
+    // 前一个字符 -> tables 的下标
+    groups: HashMap<char, usize>,
+}
+
+impl ContextModel {
+    fn table_for(&self, prev: Option<char>) -> &HuffmanNode {
+        match prev.and_then(|ch| self.groups.get(&ch)) {
+            Some(&index) => &self.tables[index],
+            None => &self.fallback,
+        }
+    }
+}
+
+fn code_table(root: &HuffmanNode) -> HashMap<char, String> {
+    let mut codes = HashMap::new();
+    build_huffman_codes_recursive(root, &mut codes, &mut String::new());
+    codes
+}
+
+fn build_context_hashmaps(contents: &str) -> HashMap<char, HashMap<char, usize>> {
+    let mut contexts: HashMap<char, HashMap<char, usize>> = HashMap::new();
+    let mut chars = contents.chars();
+    if let Some(mut prev) = chars.next() {
+        for ch in chars {
+            *contexts.entry(prev).or_default().entry(ch).or_insert(0) += 1;
+            prev = ch;
+        }
+    }
+    contexts
+}
+
+pub fn build_model(contents: &str) -> ContextModel {
+    let fallback = build_huffman_tree(&build_hashmap(contents));
+    let fallback_codes = code_table(&fallback);
+
+    // 按上下文排序，保证同样的输入得到同样的文件
+    let mut contexts: Vec<_> = build_context_hashmaps(contents).into_iter().collect();
+    contexts.sort_by_key(|(prev, _)| *prev);
+
+    let mut tables = Vec::new();
+    let mut groups = HashMap::new();
+    for (prev, hashmap) in contexts {
+        let tree = build_huffman_tree(&hashmap);
+        let codes = code_table(&tree);
+        let mut own_bits = (serialize_tree(&tree).len() + 8) * 8;
+        let mut fallback_bits = 0;
+        for (ch, freq) in hashmap.iter() {
+            own_bits += freq * codes[ch].len();
+            fallback_bits += freq * fallback_codes[ch].len();
+        }
+        if own_bits < fallback_bits {
+            groups.insert(prev, tables.len());
+            tables.push(*tree);
+        }
+    }
+
+    ContextModel { fallback, tables, groups }
+}
+
+pub fn encode(contents: &str, model: &ContextModel) -> String {
+    let fallback_codes = code_table(&model.fallback);
+    let codes: Vec<_> = model.tables.iter().map(code_table).collect();
+
+    let mut bits = String::new();
+    let mut prev = None;
+    for ch in contents.chars() {
+        let code = match prev.and_then(|p| model.groups.get(&p)) {
+            Some(&index) => &codes[index][&ch],
+            None => &fallback_codes[&ch],
+        };
+        bits.push_str(code);
+        prev = Some(ch);
+    }
+    bits
+}
+
+pub fn decode(bits: &str, model: &ContextModel) -> Result<String, Error> {
+    let mut decoded = String::new();
+    let mut node = model.table_for(None);
+    for bit in bits.chars() {
+        if node.ch.is_none() {
+            node = match bit {
+                '0' => node.left.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
+                '1' => node.right.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
+                _ => return Err(Error::Corrupt("Invalid bit")),
+            };
+        }
+        // 只有一种字符的表，根节点就是叶子，每个字符占一个比特
+        if let Some(ch) = node.ch {
+            decoded.push(ch);
+            node = model.table_for(Some(ch));
+        }
+    }
+    Ok(decoded)
+}
+
+// 格式: 共享表长度(u32) | 共享表 | 上下文个数(u32) | { 上下文字符(u32) | 表长度(u32) | 表 }...
+pub fn serialize_model(model: &ContextModel) -> Vec<u8> {
+    let mut out = Vec::new();
+    let fallback = serialize_tree(&model.fallback);
+    out.extend_from_slice(&(fallback.len() as u32).to_le_bytes());
+    out.extend_from_slice(&fallback);
+
+    let mut groups: Vec<_> = model.groups.iter().collect();
+    groups.sort_by_key(|(_, index)| **index);
+    out.extend_from_slice(&(groups.len() as u32).to_le_bytes());
+    for (prev, index) in groups {
+        let tree = serialize_tree(&model.tables[*index]);
+        out.extend_from_slice(&(*prev as u32).to_le_bytes());
+        out.extend_from_slice(&(tree.len() as u32).to_le_bytes());
+        out.extend_from_slice(&tree);
+    }
+    out
+}
+
+pub fn deserialize_model(bytes: &[u8]) -> Result<ContextModel, Error> {
+    let mut pos = 0;
+    let len = read_u32(bytes, &mut pos)? as usize;
+    let tree = bytes.get(pos..pos + len).ok_or(Error::Corrupt("Truncated context model"))?;
+    let fallback = Box::new(HuffmanNode::deserialize(tree)?);
+    pos += len;
+
+    let count = read_u32(bytes, &mut pos)? as usize;
+    let mut tables = Vec::new();
+    let mut groups = HashMap::new();
+    for index in 0..count {
+        let prev = char::from_u32(read_u32(bytes, &mut pos)?).ok_or(Error::Corrupt("Invalid context char"))?;
+        let len = read_u32(bytes, &mut pos)? as usize;
+        let tree = bytes.get(pos..pos + len).ok_or(Error::Corrupt("Truncated context model"))?;
+        tables.push(HuffmanNode::deserialize(tree)?);
+        groups.insert(prev, index);
+        pos += len;
+    }
+
+    Ok(ContextModel { fallback, tables, groups })
+}
+
+fn read_u32(bytes: &[u8], pos: &mut usize) -> Result<u32, Error> {
+    let value = bytes.get(*pos..*pos + 4).ok_or(Error::Corrupt("Truncated context model"))?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(value.try_into().unwrap()))
+}
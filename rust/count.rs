@@ -0,0 +1,103 @@
+// 多线程统计字符频率。输入按线程数切成几段（文本在字符边界上切），每个线程数到自己的数组里，
+// 最后把各段的结果加起来。码点小于 256 的字符（ASCII、Latin-1 和按字节编码的输入）用数组计数，
+// 只有其它字符才进 HashMap。每段至少 PART_SIZE 字节，小输入不开线程。
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::thread;
+
+use super::{huffman, progress};
+
+pub const PART_SIZE: usize = 1 << 22;
+
+pub struct Frequencies {
+    low: [usize; 256],
+    high: HashMap<char, usize>,
+}
+
+impl Default for Frequencies {
+    fn default() -> Frequencies {
+        Frequencies { low: [0; 256], high: HashMap::new() }
+    }
+}
+
+impl Frequencies {
+    fn add_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            match self.low.get_mut(ch as usize) {
+                Some(count) => *count += 1,
+                None => *self.high.entry(ch).or_insert(0) += 1,
+            }
+        }
+    }
+
+    fn add_bytes(&mut self, bytes: &[u8]) {
+        huffman::count(bytes, &mut self.low);
+    }
+
+    pub fn merge(&mut self, other: Frequencies) {
+        for (count, other) in self.low.iter_mut().zip(other.low.iter()) {
+            *count += other;
+        }
+        for (ch, freq) in other.high {
+            *self.high.entry(ch).or_insert(0) += freq;
+        }
+    }
+
+    // 码点小于 256 的字符的次数，按字节统计时就是每个字节的次数
+    pub fn bytes(&self) -> &[usize; 256] {
+        &self.low
+    }
+
+    pub fn into_hashmap(self) -> HashMap<char, usize> {
+        let mut hashmap = self.high;
+        for (i, &count) in self.low.iter().enumerate().filter(|(_, count)| **count > 0) {
+            hashmap.insert(char::from(i as u8), count);
+        }
+        hashmap
+    }
+}
+
+// 机器的核数，取不到时是 1。Linux 上每次查询都要读 cgroup 的配置，只查一次
+pub fn threads() -> usize {
+    static THREADS: OnceLock<usize> = OnceLock::new();
+    *THREADS.get_or_init(|| thread::available_parallelism().map_or(1, |n| n.get()))
+}
+
+// 每段至少 PART_SIZE 字节，最多 threads 段
+fn part_size(len: usize, threads: usize) -> usize {
+    len.div_ceil(threads.max(1)).max(PART_SIZE)
+}
+
+// 统计文本里每个字符出现的次数，最多用 threads 个线程
+pub fn count_text(text: &str, threads: usize) -> Frequencies {
+    let parts: Vec<&str> = progress::chunks_of(text, part_size(text.len(), threads)).collect();
+    count_parts(&parts, Frequencies::add_text)
+}
+
+// 统计每个字节出现的次数，字节 b 记在字符 b 上，和按字节编码时一样
+pub fn count_bytes(bytes: &[u8], threads: usize) -> Frequencies {
+    let parts: Vec<&[u8]> = bytes.chunks(part_size(bytes.len(), threads)).collect();
+    count_parts(&parts, Frequencies::add_bytes)
+}
+
+fn count_parts<T: Sync + ?Sized>(parts: &[&T], add: fn(&mut Frequencies, &T)) -> Frequencies {
+    let mut total = Frequencies::default();
+    if parts.len() <= 1 {
+        for part in parts {
+            add(&mut total, part);
+        }
+        return total;
+    }
+    thread::scope(|scope| {
+        let handles: Vec<_> = parts.iter().map(|part| scope.spawn(move || {
+            let mut frequencies = Frequencies::default();
+            add(&mut frequencies, part);
+            frequencies
+        })).collect();
+        for handle in handles {
+            total.merge(handle.join().expect("Counting thread panicked"));
+        }
+    });
+    total
+}
@@ -0,0 +1,77 @@
+// 压缩、解压和各个容器格式共用的错误类型。命令行把错误信息写到标准错误，按种类退出：
+//   0    成功
+//   1    命令行参数有误，包括输出文件已存在、解压时没给压缩用的 --table
+//   2    读写文件出错
+//   3    输入不是要求的格式（不是压缩文件、编码表或归档）
+//   4    压缩数据损坏：截断、树或索引不合法、解出来的长度不对
+//   5    格式版本或功能不支持，通常是更新的版本写的文件
+//   6    超出实现的限制，比如树太深
+//   130  被 Ctrl-C 中断，没有写输出
+
+use std::fmt;
+use std::io;
+
+use super::huffman::CoreError;
+
+#[derive(Debug)]
+pub enum Error {
+    Usage(String),
+    Io(String, io::Error),
+    Format(&'static str),
+    Corrupt(&'static str),
+    UnsupportedVersion(u8),
+    UnsupportedFeature(&'static str),
+    Limit(&'static str),
+    // 压缩时用的编码表的哈希
+    MissingTable(u64),
+    Cancelled,
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) | Error::MissingTable(_) => 1,
+            Error::Io(..) => 2,
+            Error::Format(_) => 3,
+            Error::Corrupt(_) => 4,
+            Error::UnsupportedVersion(_) | Error::UnsupportedFeature(_) => 5,
+            Error::Limit(_) => 6,
+            Error::Cancelled => 130,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Io(what, e) => write!(f, "{}: {}", what, e),
+            Error::Format(message) => write!(f, "{}", message),
+            Error::Corrupt(message) => write!(f, "Corrupt data: {}", message),
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported format version {}", version),
+            Error::UnsupportedFeature(message) => write!(f, "Unsupported: {}", message),
+            Error::Limit(message) => write!(f, "Limit exceeded: {}", message),
+            Error::MissingTable(hash) => write!(f, "Compressed with code table {:016x}, give it with --table", hash),
+            Error::Cancelled => write!(f, "Interrupted, no output written"),
+        }
+    }
+}
+
+// no_std 核心只在解码压缩数据时出错
+impl From<CoreError> for Error {
+    fn from(e: CoreError) -> Error {
+        Error::Corrupt(match e {
+            CoreError::Empty => "Empty code table",
+            CoreError::InvalidCodes => "Invalid code table",
+            CoreError::InvalidCode => "Invalid code",
+            CoreError::Truncated => "Truncated data",
+            CoreError::OutputFull => "Decoded length does not match",
+        })
+    }
+}
+
+// 命令行出错时调用：写出错误信息，按种类退出
+pub fn exit(e: Error) -> ! {
+    eprintln!("byoct: {}", e);
+    std::process::exit(e.exit_code());
+}
@@ -0,0 +1,251 @@
+// byoct fuzz <container|tree|payload> [--iterations N] [--seed S] [--corpus dir]
+// 进程内的变异测试，不依赖 cargo-fuzz。种子语料由内置样本用各种压缩选项生成，
+// 也可以用 --corpus 加入别的 .huffman 文件。每个输入都在 catch_unwind 里跑，
+// 发生 panic 的输入保存到 crashes/ 目录，解码器只允许返回错误，不允许 panic。
+//   container  整个压缩文件交给 decompress
+//   tree       树、上下文模型、词典、预设编号和训练出来的表文件的解析
+//   payload    用合法的树和模型解码任意数据，包括 no_std 核心（huffman.rs）
+
+use std::fs;
+use std::panic;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use super::progress::Progress;
+use super::{compress, context, decode, decompress, huffman, presets, rle, seekable, serialize_tree, table, unpack_bits, words};
+use super::{build_hashmap, build_huffman_tree, error, read_header, Error, HuffmanNode, Metadata, Options, Table};
+
+// 超过这个长度的变异结果截断，避免单个输入跑得太久
+const MAX_INPUT_LEN: usize = 4096;
+
+const SAMPLES: &[&str] = &[
+    "",
+    "a",
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab",
+    "abracadabra",
+    "the quick brown fox jumps over the lazy dog, the lazy dog sleeps.\n",
+    "{\"id\": 42, \"name\": \"sensor\", \"ok\": true, \"tags\": [\"a\", \"b\"]}",
+    "Ünïcödé ☃ \u{ffff} 😀 \"quotes\" \\backslash\\ {braces}",
+];
+
+static PANIC_MESSAGE: Mutex<String> = Mutex::new(String::new());
+
+// 用前面一半样本训练的表，另一半样本里的字符要走转义
+fn sample_table_file() -> Vec<u8> {
+    let samples: Vec<String> = SAMPLES[..4].iter().map(|sample| sample.to_string()).collect();
+    table::train(&samples)
+}
+
+fn sample_table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| Table::parse(&sample_table_file()).unwrap())
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+fn option_sets() -> Vec<Options> {
+    vec![
+        Options::default(),
+        Options { rle: true, ..Options::default() },
+        Options { order1: true, ..Options::default() },
+        Options { words: true, ..Options::default() },
+        Options { preset: Some(1), ..Options::default() },
+        Options { table: Some(Table::parse(&sample_table_file()).unwrap()), ..Options::default() },
+        Options { rle: true, order1: true, words: true, ..Options::default() },
+        Options {
+            metadata: Some(Metadata { name: Some(String::from("sample.txt")), mode: Some(0o644), mtime: Some(1_700_000_000), size: None }),
+            ..Options::default()
+        },
+    ]
+}
+
+// 把压缩文件拆成 (树这一段, 数据这一段)，跳过元数据；不是普通格式的文件返回 None
+fn split_container(bytes: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (_, _, pos) = read_header(bytes).ok()?;
+    let tree_len = u32::from_le_bytes(bytes.get(pos..pos + 4)?.try_into().unwrap()) as usize;
+    let tree = bytes.get(pos + 4..pos + 4 + tree_len)?;
+    let data = bytes.get(pos + 12 + tree_len..)?;
+    Some((tree.to_vec(), data.to_vec()))
+}
+
+fn seeds(target: &str, corpus: Option<&String>) -> Vec<Vec<u8>> {
+    let mut containers = Vec::new();
+    for sample in SAMPLES {
+        for options in option_sets() {
+            containers.push(compress(sample.as_bytes(), &options));
+        }
+        // 可随机访问的格式：共用一张表和每块一张表
+        for block_tables in [false, true] {
+            containers.push(seekable::compress(sample.as_bytes(), 8, block_tables, &mut Progress::default()).unwrap());
+        }
+    }
+    // 总长度和块的比特数都改成 2^42，块数据只有几十个字节：解码前要先用块的实际长度检查比特数，不能按它分配输出
+    let mut crafted = seekable::compress(SAMPLES[4].as_bytes(), 1 << 20, false, &mut Progress::default()).unwrap();
+    let trailer = crafted.len() - 24;
+    let index_offset = u64::from_le_bytes(crafted[trailer..trailer + 8].try_into().unwrap()) as usize;
+    let block = u64::from_le_bytes(crafted[index_offset + 8..index_offset + 16].try_into().unwrap()) as usize;
+    crafted[block..block + 8].copy_from_slice(&(1u64 << 42).to_le_bytes());
+    crafted[trailer + 12..trailer + 20].copy_from_slice(&(1u64 << 42).to_le_bytes());
+    containers.push(crafted);
+    if let Some(dir) = corpus {
+        let entries = fs::read_dir(dir).unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", dir), e)));
+        for entry in entries.flatten() {
+            if let Ok(bytes) = fs::read(entry.path()) {
+                containers.push(bytes);
+            }
+        }
+    }
+
+    match target {
+        "container" => containers,
+        "tree" => {
+            let mut seeds: Vec<Vec<u8>> = containers.iter()
+                .filter_map(|bytes| split_container(bytes))
+                .map(|(tree, _)| tree)
+                .collect();
+            for sample in SAMPLES.iter().filter(|sample| !sample.is_empty()) {
+                seeds.push(serialize_tree(&build_huffman_tree(&build_hashmap(sample))));
+                seeds.push(words::serialize_dictionary(&words::encode(sample).unwrap().0));
+            }
+            seeds.push(sample_table_file());
+            seeds
+        },
+        _ => containers.iter().filter_map(|bytes| split_container(bytes)).map(|(_, data)| data).collect(),
+    }
+}
+
+fn mutate(rng: &mut Rng, input: &[u8], seeds: &[Vec<u8>]) -> Vec<u8> {
+    const INTERESTING: &[u8] = &[0, 1, 0x7f, 0x80, 0xff, b'{', b'}', b'"', b':', b',', b'\\', b'0', b'9'];
+    let mut data = input.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        let pos = rng.below(data.len() + 1);
+        let last = data.len().saturating_sub(1);
+        match rng.below(7) {
+            0 if !data.is_empty() => data[pos.min(last)] ^= 1 << rng.below(8),
+            1 if !data.is_empty() => data[pos.min(last)] = INTERESTING[rng.below(INTERESTING.len())],
+            2 => data.insert(pos, rng.next() as u8),
+            3 if pos < data.len() => {
+                data.remove(pos);
+            },
+            4 => data.truncate(pos),
+            5 if !data.is_empty() => {
+                // 复制一段数据插到别的位置，容易产生嵌套很深的树
+                let start = rng.below(data.len());
+                let end = (start + 1 + rng.below(64)).min(data.len());
+                let chunk = data[start..end].to_vec();
+                let at = rng.below(data.len() + 1);
+                data.splice(at..at, chunk);
+            },
+            _ => {
+                // 和另一个种子拼接
+                let other = &seeds[rng.below(seeds.len())];
+                let cut = rng.below(other.len() + 1);
+                data.truncate(pos);
+                data.extend_from_slice(&other[cut..]);
+            },
+        }
+    }
+    data.truncate(MAX_INPUT_LEN);
+    data
+}
+
+fn run_target(target: &str, data: &[u8]) {
+    match target {
+        "container" => {
+            let _ = decompress(data, std::slice::from_ref(sample_table()));
+            let _ = seekable::decompress_range(data, 5, 10, &mut Progress::default());
+        },
+        "tree" => {
+            // 解析成功的树还要能安全地解码任意比特
+            if let Ok(root) = HuffmanNode::deserialize(data) {
+                let _ = decode(&"0110100111".repeat(8), &root);
+            }
+            let _ = context::deserialize_model(data);
+            let _ = words::deserialize_dictionary(data);
+            if let Some(id) = data.first() {
+                let _ = presets::build_tree(*id);
+            }
+            if let Ok(table) = Table::parse(data) {
+                let _ = presets::decode(&"0110100111".repeat(8), &table.root);
+            }
+        },
+        _ => {
+            let bits = unpack_bits(data, data.len() * 8).unwrap();
+            let root = build_huffman_tree(&build_hashmap(SAMPLES[4]));
+            if let Ok(text) = decode(&bits, &root) {
+                let _ = rle::decode(&text);
+                let (dictionary, _) = words::encode(SAMPLES[4]).unwrap();
+                let _ = words::decode(&text, &dictionary);
+            }
+            let model = context::build_model(SAMPLES[4]);
+            let _ = context::decode(&bits, &model);
+            let _ = presets::decode(&bits, &presets::build_tree(3).unwrap());
+            let mut counts = [0; 256];
+            huffman::count(SAMPLES[4].as_bytes(), &mut counts);
+            let _ = huffman::decode(data, data.len() * 8, &huffman::Codes::from_counts(&counts).unwrap());
+        },
+    }
+}
+
+pub fn run(args: &[String]) {
+    let mut target = None;
+    let mut iterations = 100_000;
+    let mut seed: u64 = 0x6A09_E667_F3BC_C908;
+    let mut corpus = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--iterations" => iterations = iter.next().and_then(|n| n.parse().ok()).unwrap_or(iterations),
+            "--seed" => seed = iter.next().and_then(|n| n.parse().ok()).unwrap_or(seed),
+            "--corpus" => corpus = iter.next(),
+            _ => target = Some(arg.as_str()),
+        }
+    }
+    let target = match target {
+        Some(target @ ("container" | "tree" | "payload")) => target,
+        _ => {
+            println!("Usage: byoct fuzz <container|tree|payload> [--iterations N] [--seed S] [--corpus dir]");
+            std::process::exit(1);
+        }
+    };
+
+    let seeds = seeds(target, corpus);
+    panic::set_hook(Box::new(|info| {
+        *PANIC_MESSAGE.lock().unwrap() = info.to_string();
+    }));
+
+    let mut rng = Rng(seed);
+    let mut crashes = 0;
+    for i in 0..iterations {
+        let input = &seeds[rng.below(seeds.len())];
+        let data = mutate(&mut rng, input, &seeds);
+        if panic::catch_unwind(|| run_target(target, &data)).is_err() {
+            crashes += 1;
+            let path = Path::new("crashes").join(format!("crash-{}-{}", target, i));
+            if let Err(e) = fs::create_dir_all("crashes").and_then(|_| fs::write(&path, &data)) {
+                error::exit(Error::Io(format!("Error writing {}", path.display()), e));
+            }
+            println!("crash at iteration {}: {}", i, PANIC_MESSAGE.lock().unwrap());
+            println!("    saved to {}", path.display());
+        }
+    }
+    let _ = panic::take_hook();
+
+    println!("{} iterations of {} with seed {}, {} crashes", iterations, target, seed, crashes);
+    if crashes > 0 {
+        std::process::exit(1);
+    }
+}
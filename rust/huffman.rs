@@ -0,0 +1,338 @@
+// 不依赖 std 的 Huffman 编解码核心，只用 core 和 alloc，可以放到单片机上用。
+// 字母表固定是 256 个字节，频率、码表和树都放在定长数组里，比特读写直接操作字节切片，
+// 高位在前，和 byoct1.rs 的 pack_bits 一样。读写文件、容器格式和多线程统计留在 std 这一层。
+// 建树的顺序和 byoct1.rs 的 build_huffman_tree 完全一样，同样的频率得到同样的树和码。
+// 不是 UTF-8、只用 order-0 的输入和可随机访问的格式都用这里建树和编解码，std 那一层只负责把树序列化。
+// test/nostd.rs 把这个文件放进一个 #![no_std] 的程序里单独编译运行。
+
+use alloc::vec::Vec;
+
+pub const SYMBOLS: usize = 256;
+// 叶子最多 256 个，内部节点最多 255 个
+const MAX_NODES: usize = 2 * SYMBOLS - 1;
+// 码长上限，和 byoct1.rs 的 MAX_TREE_DEPTH 一样
+pub const MAX_CODE_LEN: usize = 128;
+const NONE: u16 = u16::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreError {
+    // 没有任何符号，建不出树
+    Empty,
+    // 码表不是完整的前缀码，或者码太长
+    InvalidCodes,
+    // 比特流走到了树里不存在的分支
+    InvalidCode,
+    // 比特流在一个码的中间结束，或者比数据切片还长
+    Truncated,
+    // 输出切片放不下
+    OutputFull,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    freq: usize,
+    // 内部节点是 NONE
+    symbol: u16,
+    children: [u16; 2],
+}
+
+const EMPTY_NODE: Node = Node { freq: 0, symbol: NONE, children: [NONE; 2] };
+
+// 一棵 Huffman 树和由它得到的码表
+pub struct Codes {
+    nodes: [Node; MAX_NODES],
+    node_count: usize,
+    root: u16,
+    // 码的低 lengths[b] 位，先输出高位
+    codes: [u128; SYMBOLS],
+    lengths: [u8; SYMBOLS],
+}
+
+// 统计每个字节出现的次数，加到 counts 上。
+// 同一个字节连续出现时，只用一个数组每次加一都要等上一次写完，轮流写四个数组可以让相邻的加法并行
+pub fn count(bytes: &[u8], counts: &mut [usize; SYMBOLS]) {
+    let mut lanes = [[0usize; SYMBOLS]; 4];
+    let mut quads = bytes.chunks_exact(4);
+    for quad in quads.by_ref() {
+        lanes[0][quad[0] as usize] += 1;
+        lanes[1][quad[1] as usize] += 1;
+        lanes[2][quad[2] as usize] += 1;
+        lanes[3][quad[3] as usize] += 1;
+    }
+    for &byte in quads.remainder() {
+        lanes[0][byte as usize] += 1;
+    }
+    for (i, count) in counts.iter_mut().enumerate() {
+        *count += lanes[0][i] + lanes[1][i] + lanes[2][i] + lanes[3][i];
+    }
+}
+
+impl Codes {
+    fn empty() -> Codes {
+        Codes { nodes: [EMPTY_NODE; MAX_NODES], node_count: 0, root: NONE, codes: [0; SYMBOLS], lengths: [0; SYMBOLS] }
+    }
+
+    fn push(&mut self, node: Node) -> u16 {
+        self.nodes[self.node_count] = node;
+        self.node_count += 1;
+        (self.node_count - 1) as u16
+    }
+
+    // 排序用的键和 build_huffman_tree 的 (freq, ch) 一样：频率相同时内部节点排在叶子前面，叶子按字节排
+    fn key(&self, index: u16) -> (usize, u16) {
+        let node = &self.nodes[index as usize];
+        (node.freq, node.symbol.wrapping_add(1))
+    }
+
+    // 按频率建树，频率为 0 的字节不进树
+    pub fn from_counts(counts: &[usize; SYMBOLS]) -> Result<Codes, CoreError> {
+        let mut codes = Codes::empty();
+        let mut queue = [0u16; SYMBOLS];
+        let mut queued = 0;
+        for (symbol, &freq) in counts.iter().enumerate().filter(|(_, freq)| **freq > 0) {
+            queue[queued] = codes.push(Node { freq, symbol: symbol as u16, children: [NONE; 2] });
+            queued += 1;
+        }
+        if queued == 0 {
+            return Err(CoreError::Empty);
+        }
+        queue[..queued].sort_unstable_by_key(|&index| codes.key(index));
+
+        // 每次取出最小的两个合并，新节点插到所有键相同的节点后面，和稳定排序的结果一样
+        while queued > 1 {
+            let (left, right) = (queue[0], queue[1]);
+            queue.copy_within(2..queued, 0);
+            queued -= 2;
+            let freq = codes.nodes[left as usize].freq.saturating_add(codes.nodes[right as usize].freq);
+            let parent = codes.push(Node { freq, symbol: NONE, children: [left, right] });
+            let key = codes.key(parent);
+            let pos = queue[..queued].partition_point(|&index| codes.key(index) <= key);
+            queue.copy_within(pos..queued, pos + 1);
+            queue[pos] = parent;
+            queued += 1;
+        }
+        codes.root = queue[0];
+        codes.assign(codes.root, 0, 0)?;
+        Ok(codes)
+    }
+
+    // 从每个字节的码（低 len 位）建树，用来解码已有的码表。码必须是完整的前缀码；
+    // 只有一个字节时 byoct1.rs 给它的码是 "0"，树只有一个叶子
+    pub fn from_codes(entries: impl IntoIterator<Item = (u8, u128, u8)>) -> Result<Codes, CoreError> {
+        let mut codes = Codes::empty();
+        codes.root = codes.push(EMPTY_NODE);
+        let mut count = 0;
+        let mut single = 0;
+        for (symbol, code, len) in entries {
+            let len = len as usize;
+            if len == 0 || len > MAX_CODE_LEN || codes.lengths[symbol as usize] != 0 {
+                return Err(CoreError::InvalidCodes);
+            }
+            codes.codes[symbol as usize] = code;
+            codes.lengths[symbol as usize] = len as u8;
+            count += 1;
+            single = symbol;
+            let mut node = codes.root;
+            for i in (0..len).rev() {
+                if codes.nodes[node as usize].symbol != NONE {
+                    return Err(CoreError::InvalidCodes);
+                }
+                let bit = (code >> i & 1) as usize;
+                let child = match codes.nodes[node as usize].children[bit] {
+                    NONE if codes.node_count == MAX_NODES => return Err(CoreError::InvalidCodes),
+                    NONE => {
+                        let child = codes.push(EMPTY_NODE);
+                        codes.nodes[node as usize].children[bit] = child;
+                        child
+                    },
+                    child => child,
+                };
+                node = child;
+            }
+            // 已经有叶子或者子树的位置不能再放叶子
+            let node = &mut codes.nodes[node as usize];
+            if node.symbol != NONE || node.children != [NONE; 2] {
+                return Err(CoreError::InvalidCodes);
+            }
+            node.symbol = symbol as u16;
+        }
+        if count == 1 && codes.lengths[single as usize] == 1 && codes.codes[single as usize] == 0 {
+            codes.node_count = 0;
+            codes.root = codes.push(Node { freq: 0, symbol: single as u16, children: [NONE; 2] });
+            return Ok(codes);
+        }
+        if count == 0 {
+            return Err(CoreError::Empty);
+        }
+        let complete = codes.nodes[..codes.node_count].iter()
+            .all(|node| node.symbol != NONE || node.children.iter().all(|&child| child != NONE));
+        if !complete {
+            return Err(CoreError::InvalidCodes);
+        }
+        Ok(codes)
+    }
+
+    fn assign(&mut self, index: u16, code: u128, len: usize) -> Result<(), CoreError> {
+        let node = self.nodes[index as usize];
+        if node.symbol != NONE {
+            // 只有一种字节时根节点就是叶子，也要给它一个比特
+            self.codes[node.symbol as usize] = code;
+            self.lengths[node.symbol as usize] = len.max(1) as u8;
+            return Ok(());
+        }
+        if len == MAX_CODE_LEN {
+            return Err(CoreError::InvalidCodes);
+        }
+        self.assign(node.children[0], code << 1, len + 1)?;
+        self.assign(node.children[1], code << 1 | 1, len + 1)
+    }
+
+    // 字节的码（低 len 位）和码长，不在树里的字节返回 None
+    pub fn code(&self, symbol: u8) -> Option<(u128, u8)> {
+        let len = self.lengths[symbol as usize];
+        if len == 0 { None } else { Some((self.codes[symbol as usize], len)) }
+    }
+
+    // 给 std 那一层转换成自己的树用
+    pub fn root(&self) -> u16 {
+        self.root
+    }
+
+    // 节点的 (频率, 叶子的字节, 内部节点的两个孩子)
+    pub fn node(&self, index: u16) -> (usize, Option<u8>, Option<[u16; 2]>) {
+        let node = &self.nodes[index as usize];
+        if node.symbol != NONE {
+            (node.freq, Some(node.symbol as u8), None)
+        } else {
+            (node.freq, None, Some(node.children))
+        }
+    }
+
+    // 编码 input 需要的比特数，输入里有不在树里的字节时返回 None
+    pub fn encoded_bits(&self, input: &[u8]) -> Option<usize> {
+        let mut bits = 0;
+        for &byte in input {
+            match self.lengths[byte as usize] {
+                0 => return None,
+                len => bits += len as usize,
+            }
+        }
+        Some(bits)
+    }
+}
+
+// 往字节切片里按高位在前写比特
+pub struct BitWriter<'a> {
+    out: &'a mut [u8],
+    bits: usize,
+}
+
+impl<'a> BitWriter<'a> {
+    pub fn new(out: &'a mut [u8]) -> BitWriter<'a> {
+        BitWriter { out, bits: 0 }
+    }
+
+    // 写 code 的低 len 位
+    pub fn write(&mut self, code: u128, len: u8) -> Result<(), CoreError> {
+        let mut left = len as usize;
+        if self.bits + left > self.out.len() * 8 {
+            return Err(CoreError::OutputFull);
+        }
+        while left > 0 {
+            let offset = self.bits % 8;
+            if offset == 0 {
+                self.out[self.bits / 8] = 0;
+            }
+            let n = left.min(8 - offset);
+            let chunk = (code >> (left - n)) as u8 & ((1u16 << n) - 1) as u8;
+            self.out[self.bits / 8] |= chunk << (8 - offset - n);
+            self.bits += n;
+            left -= n;
+        }
+        Ok(())
+    }
+
+    // 已经写了多少比特
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+}
+
+// 从字节切片里按高位在前读比特，最多读 bit_len 个
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    bit_len: usize,
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8], bit_len: usize) -> Result<BitReader<'a>, CoreError> {
+        if data.len() * 8 < bit_len {
+            return Err(CoreError::Truncated);
+        }
+        Ok(BitReader { data, bit_len, pos: 0 })
+    }
+
+    pub fn read(&mut self) -> Option<u8> {
+        if self.pos == self.bit_len {
+            return None;
+        }
+        let bit = self.data[self.pos / 8] >> (7 - self.pos % 8) & 1;
+        self.pos += 1;
+        Some(bit)
+    }
+}
+
+// 编码到 out 里，返回写了多少比特
+pub fn encode_into(input: &[u8], codes: &Codes, out: &mut [u8]) -> Result<usize, CoreError> {
+    let mut writer = BitWriter::new(out);
+    for &byte in input {
+        let (code, len) = codes.code(byte).ok_or(CoreError::InvalidCode)?;
+        writer.write(code, len)?;
+    }
+    Ok(writer.bits())
+}
+
+// 返回 (打包好的比特, 比特数)
+pub fn encode(input: &[u8], codes: &Codes) -> Result<(Vec<u8>, usize), CoreError> {
+    let bits = codes.encoded_bits(input).ok_or(CoreError::InvalidCode)?;
+    let mut out = alloc::vec![0; bits.div_ceil(8)];
+    encode_into(input, codes, &mut out)?;
+    Ok((out, bits))
+}
+
+// 解码 data 的前 bit_len 个比特到 out 里，返回解出了多少字节
+pub fn decode_into(data: &[u8], bit_len: usize, codes: &Codes, out: &mut [u8]) -> Result<usize, CoreError> {
+    let mut reader = BitReader::new(data, bit_len)?;
+    let root = codes.root;
+    let mut written = 0;
+    let mut node = root;
+    while let Some(bit) = reader.read() {
+        // 只有一个叶子时每个比特都是这个字节
+        if codes.nodes[root as usize].symbol == NONE {
+            node = codes.nodes[node as usize].children[bit as usize];
+            if node == NONE {
+                return Err(CoreError::InvalidCode);
+            }
+        }
+        let symbol = codes.nodes[node as usize].symbol;
+        if symbol != NONE {
+            *out.get_mut(written).ok_or(CoreError::OutputFull)? = symbol as u8;
+            written += 1;
+            node = root;
+        }
+    }
+    if node != root {
+        return Err(CoreError::Truncated);
+    }
+    Ok(written)
+}
+
+// 解码成 Vec。每个字节至少占一个比特，输出不会超过 bit_len 字节
+pub fn decode(data: &[u8], bit_len: usize, codes: &Codes) -> Result<Vec<u8>, CoreError> {
+    BitReader::new(data, bit_len)?;
+    let mut out = alloc::vec![0; bit_len];
+    let written = decode_into(data, bit_len, codes, &mut out)?;
+    out.truncate(written);
+    Ok(out)
+}
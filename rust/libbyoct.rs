@@ -0,0 +1,15 @@
+// C 库的入口：把 byoct1.rs 整个放进库的根模块（模块的路径和 crate:: 都不变），导出 capi.rs 里的函数，
+// 头文件是 byoct.h。capi 只编译进库，命令行的程序不导出这些符号。
+// 库里不会调用 main，在下面引用一次，命令行用到的代码就不算没用；真正没用的代码照样报 dead_code。
+//
+// 在 rust 目录下构建（Linux 上得到 libbyoct.so 和 libbyoct.a）：
+//   rustc --edition 2021 -O --crate-type cdylib,staticlib libbyoct.rs --print native-static-libs
+// 链接静态库时还要加上最后一行打印出来的系统库，比如 -lgcc_s -lutil -lrt -lpthread -lm -ldl -lc
+
+#![crate_name = "byoct"]
+
+include!("byoct1.rs");
+
+mod capi;
+
+const _: fn() = main;
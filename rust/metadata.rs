@@ -0,0 +1,151 @@
+// 压缩文件头里可选的元数据：原始文件名、Unix 权限和修改时间，和 gzip 的 FNAME/MTIME 类似，
+// 还有原始长度，解压时用来预先分配输出和检查结果。
+// 格式: 字段标志(u8) | [名字长度(u16) | 名字] | [权限(u32)] | [修改时间(i64，Unix 秒)] | [原始长度(u64)]
+// --no-name 不记录名字和修改时间，只保留权限。
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::Error;
+
+const FIELD_NAME: u8 = 0x01;
+const FIELD_MODE: u8 = 0x02;
+const FIELD_MTIME: u8 = 0x04;
+const FIELD_SIZE: u8 = 0x08;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub mode: Option<u32>,
+    pub mtime: Option<i64>,
+    pub size: Option<u64>,
+}
+
+impl Metadata {
+    pub fn from_file(path: &Path, with_name: bool) -> std::io::Result<Metadata> {
+        let info = fs::metadata(path)?;
+        let mut metadata = Metadata { mode: mode_of(&info), ..Metadata::default() };
+        if with_name {
+            metadata.name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+            metadata.mtime = info.modified().ok().map(|time| match time.duration_since(UNIX_EPOCH) {
+                Ok(after) => after.as_secs() as i64,
+                Err(before) => -(before.duration().as_secs() as i64),
+            });
+        }
+        Ok(metadata)
+    }
+
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        let mut fields = 0;
+        if self.name.is_some() {
+            fields |= FIELD_NAME;
+        }
+        if self.mode.is_some() {
+            fields |= FIELD_MODE;
+        }
+        if self.mtime.is_some() {
+            fields |= FIELD_MTIME;
+        }
+        if self.size.is_some() {
+            fields |= FIELD_SIZE;
+        }
+        out.push(fields);
+        if let Some(name) = &self.name {
+            // 名字最长 u16::MAX 字节，在字符边界截断
+            let mut len = name.len().min(u16::MAX as usize);
+            while !name.is_char_boundary(len) {
+                len -= 1;
+            }
+            out.extend_from_slice(&(len as u16).to_le_bytes());
+            out.extend_from_slice(&name.as_bytes()[..len]);
+        }
+        if let Some(mode) = self.mode {
+            out.extend_from_slice(&mode.to_le_bytes());
+        }
+        if let Some(mtime) = self.mtime {
+            out.extend_from_slice(&mtime.to_le_bytes());
+        }
+        if let Some(size) = self.size {
+            out.extend_from_slice(&size.to_le_bytes());
+        }
+    }
+
+    pub fn deserialize(bytes: &[u8], pos: &mut usize) -> Result<Metadata, Error> {
+        let fields = *bytes.get(*pos).ok_or(Error::Corrupt("Truncated metadata"))?;
+        *pos += 1;
+        if fields & !(FIELD_NAME | FIELD_MODE | FIELD_MTIME | FIELD_SIZE) != 0 {
+            return Err(Error::UnsupportedFeature("Unknown metadata fields"));
+        }
+        let mut metadata = Metadata::default();
+        if fields & FIELD_NAME != 0 {
+            let len = u16::from_le_bytes(take(bytes, pos, 2)?.try_into().unwrap()) as usize;
+            let name = take(bytes, pos, len)?;
+            metadata.name = Some(String::from_utf8(name.to_vec()).map_err(|_| Error::Corrupt("Invalid UTF-8 in file name"))?);
+        }
+        if fields & FIELD_MODE != 0 {
+            metadata.mode = Some(u32::from_le_bytes(take(bytes, pos, 4)?.try_into().unwrap()));
+        }
+        if fields & FIELD_MTIME != 0 {
+            metadata.mtime = Some(i64::from_le_bytes(take(bytes, pos, 8)?.try_into().unwrap()));
+        }
+        if fields & FIELD_SIZE != 0 {
+            metadata.size = Some(u64::from_le_bytes(take(bytes, pos, 8)?.try_into().unwrap()));
+        }
+        Ok(metadata)
+    }
+
+    // 记录的名字只取最后一段，不允许带路径，避免解压时写到别的目录
+    pub fn safe_name(&self) -> Option<&str> {
+        self.name.as_deref().filter(|name| {
+            !name.is_empty() && *name != "." && *name != ".." && !name.contains('/') && !name.contains('\\')
+        })
+    }
+
+    // 把权限和修改时间设置到解压出来的文件上
+    pub fn apply(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(mtime) = self.mtime {
+            let time = match mtime {
+                0.. => UNIX_EPOCH + Duration::from_secs(mtime as u64),
+                _ => UNIX_EPOCH - Duration::from_secs(mtime.unsigned_abs()),
+            };
+            fs::File::options().write(true).open(path)?.set_modified(time)?;
+        }
+        if let Some(mode) = self.mode {
+            set_mode(path, mode)?;
+        }
+        Ok(())
+    }
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], Error> {
+    let value = bytes.get(*pos..*pos + len).ok_or(Error::Corrupt("Truncated metadata"))?;
+    *pos += len;
+    Ok(value)
+}
+
+// 只保留读写执行位；setuid、setgid 和粘滞位不记录也不恢复，否则伪造的压缩文件能解出 setuid 程序
+#[cfg(unix)]
+fn mode_of(info: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(info.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn mode_of(_: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+}
+
+// 其它平台只能保留只读属性
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
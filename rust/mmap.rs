@@ -0,0 +1,96 @@
+// 输入文件的内存映射（--mmap）。大文件不用先整个复制到堆上，映射出来的切片直接交给
+// 统计频率和编码。没有外部依赖，Unix 上直接调用 libc 的 mmap/munmap，
+// 其它平台或者映射失败（比如空文件、管道）时退回到 fs::read。
+// 映射期间文件被别的进程改写，读到的内容就不确定了，和 gzip 读文件时被改写一样，不做保护。
+// 默认的 order-0 编码直接把码写进输出的字节，不是 UTF-8 的输入也按字节编码不复制，
+// 这时内存只有映射的输入加上压缩后的输出。--rle、--words、--order1、--preset 和 --table
+// 还要把输入复制成 String（不是 UTF-8 时），编码时每个比特先占一个字节，映射只省下了读文件的那一份。
+
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+pub enum Input {
+    Owned(Vec<u8>),
+    #[cfg(unix)]
+    Mapped(Mapping),
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Owned(bytes) => bytes,
+            #[cfg(unix)]
+            Input::Mapped(mapping) => mapping.as_slice(),
+        }
+    }
+}
+
+pub fn read(path: &Path, use_mmap: bool) -> io::Result<Input> {
+    #[cfg(unix)]
+    if use_mmap {
+        if let Some(mapping) = Mapping::new(path)? {
+            return Ok(Input::Mapped(mapping));
+        }
+    }
+    let _ = use_mmap;
+    fs::read(path).map(Input::Owned)
+}
+
+#[cfg(unix)]
+pub use unix::Mapping;
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io;
+    use std::os::raw::c_int;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    pub struct Mapping {
+        ptr: *mut c_void,
+        len: usize,
+    }
+
+    impl Mapping {
+        // 空文件和不能映射的文件（管道、设备）返回 None，由调用者改用普通读取
+        pub fn new(path: &Path) -> io::Result<Option<Mapping>> {
+            let file = File::open(path)?;
+            let len = match usize::try_from(file.metadata()?.len()) {
+                Ok(len) if len > 0 && file.metadata()?.is_file() => len,
+                _ => return Ok(None),
+            };
+            // 映射在文件关闭之后仍然有效
+            let ptr = unsafe { mmap(std::ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0) };
+            if ptr as isize == -1 {
+                return Ok(None);
+            }
+            Ok(Some(Mapping { ptr, len }))
+        }
+
+        pub fn as_slice(&self) -> &[u8] {
+            unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            unsafe {
+                munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
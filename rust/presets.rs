@@ -0,0 +1,132 @@
+// 内置的预设编码表。小文件自己的树可能比数据还大，
+// 使用预设表时文件头里只记一个编号，不用写树。
+// 预设表里没有的字符先写转义码，再写 21 位的码点。
+
+use std::collections::HashMap;
+
+use super::{build_huffman_codes_recursive, build_huffman_tree, Error, HuffmanNode};
+
+// 转义符号用一个非字符码点，它真的出现在输入里时同样走转义，训练出来的表（table.rs）也用它
+pub const ESCAPE: char = '\u{FFFF}';
+const CODEPOINT_BITS: usize = 21;
+
+pub fn preset_id(name: &str) -> Option<u8> {
+    match name {
+        "english" => Some(1),
+        "code" => Some(2),
+        "json" => Some(3),
+        _ => None,
+    }
+}
+
+pub fn build_tree(id: u8) -> Result<Box<HuffmanNode>, Error> {
+    let table = match id {
+        1 => ENGLISH,
+        2 => CODE,
+        3 => JSON,
+        _ => return Err(Error::UnsupportedFeature("Unknown preset")),
+    };
+    let mut hashmap: HashMap<char, usize> = table.iter().copied().collect();
+    hashmap.insert(ESCAPE, 1);
+    Ok(build_huffman_tree(&hashmap))
+}
+
+pub fn encode(contents: &str, root: &HuffmanNode) -> String {
+    let mut codes = HashMap::new();
+    build_huffman_codes_recursive(root, &mut codes, &mut String::new());
+
+    let mut bits = String::new();
+    for ch in contents.chars() {
+        match codes.get(&ch) {
+            Some(code) if ch != ESCAPE => bits.push_str(code),
+            _ => {
+                bits.push_str(&codes[&ESCAPE]);
+                bits.push_str(&format!("{:021b}", ch as u32));
+            },
+        }
+    }
+    bits
+}
+
+pub fn decode(bits: &str, root: &HuffmanNode) -> Result<String, Error> {
+    let mut decoded = String::new();
+    let mut node = root;
+    let mut pos = 0;
+    while pos < bits.len() {
+        // 用空样本训练的表只有转义一个符号，根节点就是叶子，每个符号占一个比特
+        if node.ch.is_none() {
+            node = match &bits[pos..pos + 1] {
+                "0" => node.left.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
+                "1" => node.right.as_ref().ok_or(Error::Corrupt("Invalid tree"))?,
+                _ => return Err(Error::Corrupt("Invalid bit")),
+            };
+        }
+        pos += 1;
+        match node.ch {
+            Some(ESCAPE) => {
+                let codepoint = bits.get(pos..pos + CODEPOINT_BITS).ok_or(Error::Corrupt("Truncated escape"))?;
+                let codepoint = u32::from_str_radix(codepoint, 2).map_err(|_| Error::Corrupt("Invalid bit"))?;
+                decoded.push(char::from_u32(codepoint).ok_or(Error::Corrupt("Invalid escaped char"))?);
+                pos += CODEPOINT_BITS;
+                node = root;
+            },
+            Some(ch) => {
+                decoded.push(ch);
+                node = root;
+            },
+            None => (),
+        }
+    }
+    Ok(decoded)
+}
+
+// 英文的分布来自 step1_sort.rs 对 135-0.txt 的统计（换行数是估计值），
+// 源代码和 JSON 的分布按每 100000 个字符的出现次数统计
+const ENGLISH: &[(char, usize)] = &[
+    (' ', 516353), ('e', 325664), ('t', 223000), ('a', 199732), ('o', 180974), ('h', 170220), ('n', 166862), ('i', 165211),
+    ('s', 157632), ('r', 145091), ('d', 106966), ('l', 97113), ('\n', 68000), ('u', 67391), ('c', 62741), ('m', 56000),
+    ('f', 53421), ('w', 53330), (',', 48780), ('g', 46029), ('p', 39940), ('y', 37944), ('b', 34267), ('.', 30480),
+    ('v', 24047), ('k', 14107), ('T', 12571), ('I', 10109), ('A', 7369), ('“', 7119), ('”', 7048), ('H', 6603),
+    ('M', 6212), (';', 5885), ('E', 4939), ('-', 4700), ('C', 4591), ('S', 4411), ('’', 3707), ('x', 3693),
+    ('R', 3541), ('!', 3539), ('P', 3426), ('O', 3411), ('j', 3391), ('B', 3194), ('W', 3177), ('—', 3144),
+    ('N', 3060), ('?', 2976), ('F', 2766), ('G', 2514), (':', 2501), ('L', 2454), ('J', 2447), ('q', 2398),
+    ('V', 2211), ('_', 2070), ('z', 1845), ('D', 1758), ('é', 1327), ('Y', 1239), ('U', 904), ('1', 693),
+    ('8', 404), ('X', 333), ('K', 320), ('è', 293), ('3', 239), ('2', 227), ('‘', 224), ('0', 205),
+    ('5', 190), ('(', 170), (')', 170), ('*', 169), ('[', 164), (']', 164), ('4', 160), ('É', 146),
+    ('6', 142), ('Q', 135), ('7', 132), ('9', 114), ('æ', 106), ('ê', 74), ('Z', 60), ('à', 59),
+    ('â', 56), ('ç', 48), ('î', 39), ('ü', 37), ('œ', 35), ('ô', 34), ('|', 27), ('"', 22),
+    ('ï', 18), ('ù', 18), ('Æ', 10), ('û', 9), ('\'', 7), ('/', 6), ('È', 6), ('+', 5),
+    ('À', 5), ('ë', 5), ('Œ', 3), ('$', 2), ('Ç', 2), ('Ü', 2), ('ñ', 2), ('#', 1),
+    ('%', 1), ('&', 1), ('{', 1), ('}', 1), ('½', 1), ('…', 1),
+];
+
+const CODE: &[(char, usize)] = &[
+    (' ', 29048), ('e', 7088), ('t', 4605), ('s', 3990), ('r', 3810), ('a', 3669), ('n', 3664), ('i', 3583),
+    ('o', 3412), ('\n', 2785), ('l', 2723), ('d', 1988), ('f', 1981), ('c', 1897), ('_', 1721), ('p', 1525),
+    ('u', 1477), ('m', 1434), ('.', 1240), ('h', 1203), (')', 1062), ('(', 1061), (',', 1029), ('g', 889),
+    ('"', 873), ('\'', 867), ('=', 807), (':', 774), ('b', 761), ('y', 640), ('w', 481), ('x', 424),
+    ('v', 419), ('#', 407), ('-', 383), ('k', 376), ('E', 344), ('T', 314), ('N', 274), ('0', 264),
+    ('1', 246), ('I', 243), ('S', 235), ('A', 201), ('O', 178), ('R', 177), ('[', 163), (']', 162),
+    ('C', 151), ('>', 149), ('P', 148), ('*', 142), ('D', 138), ('2', 137), ('F', 134), ('L', 126),
+    ('j', 126), ('M', 106), ('/', 105), ('U', 98), ('z', 92), ('+', 86), ('3', 84), ('%', 83),
+    ('8', 75), ('q', 71), ('\\', 70), ('5', 69), ('B', 66), ('4', 65), ('H', 63), ('V', 59),
+    ('G', 57), ('<', 56), ('6', 52), ('9', 50), ('`', 50), ('{', 45), ('}', 45), ('W', 44),
+    ('7', 32), ('K', 31), ('Y', 30), ('!', 26), ('X', 25), ('@', 20), (';', 18), ('|', 17),
+    ('?', 15), ('Z', 15), ('&', 7), ('J', 7), ('Q', 7), ('^', 6), ('$', 3), ('~', 3),
+];
+
+const JSON: &[(char, usize)] = &[
+    (' ', 23450), ('"', 8952), ('e', 5541), ('\n', 4695), ('r', 4547), ('a', 3551), ('i', 3314), ('t', 3100),
+    (',', 2954), ('.', 2909), ('g', 2778), ('l', 2552), ('d', 2507), ('n', 2404), (':', 2064), ('o', 1827),
+    ('s', 1789), ('x', 1596), ('m', 1516), ('b', 1427), ('p', 1377), ('c', 825), ('{', 790), ('}', 790),
+    ('6', 761), ('2', 759), ('u', 752), ('f', 697), ('y', 669), ('/', 569), ('1', 524), ('8', 504),
+    ('4', 494), ('5', 346), ('C', 318), ('I', 314), ('-', 306), ('S', 303), ('A', 301), ('h', 293),
+    ('P', 268), ('7', 263), ('L', 251), ('[', 242), (']', 242), ('0', 213), ('T', 211), ('$', 197),
+    ('D', 175), ('3', 171), ('E', 166), ('O', 165), ('#', 156), ('\\', 145), ('R', 142), ('N', 140),
+    ('M', 119), ('F', 118), ('V', 111), ('q', 104), ('v', 104), ('w', 96), ('j', 95), ('B', 84),
+    ('z', 83), ('9', 72), ('_', 62), ('G', 61), ('W', 61), ('k', 61), ('U', 60), ('^', 47),
+    ('K', 46), ('+', 43), ('(', 39), (')', 39), ('Z', 34), ('H', 29), ('*', 27), ('|', 22),
+    ('?', 16), ('Q', 9), ('Y', 9), ('X', 8), ('=', 7), ('@', 7), ('J', 6), ('!', 3),
+    ('\'', 2), ('\t', 1), ('%', 1), ('&', 1), (';', 1), ('<', 1), ('>', 1), ('`', 1),
+    ('~', 1),
+];
@@ -0,0 +1,149 @@
+// 压缩和解压的进度回调与取消。compress_with/decompress_with 在每个阶段开始时、
+// 以及处理完每一块数据之后调用回调，参数是 (阶段, 已处理字节数, 总字节数)，
+// 同时检查取消标志，被取消时返回 Err(Error::Cancelled)。
+// 命令行在标准错误是终端时用它画进度条，按 Ctrl-C 时通过取消令牌停下来，不留下输出文件。
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use super::Error;
+
+// 统计和编码时每处理这么多字节报告一次进度
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Counting,
+    Building,
+    Encoding,
+    Decoding,
+    Writing,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Counting => "counting",
+            Phase::Building => "building",
+            Phase::Encoding => "encoding",
+            Phase::Decoding => "decoding",
+            Phase::Writing => "writing",
+        }
+    }
+}
+
+// 可以在别的线程里调用 cancel()，克隆出来的令牌共享同一个标志
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// 参数是 (阶段, 已完成, 总量)
+pub type Callback<'a> = Box<dyn FnMut(Phase, u64, u64) + 'a>;
+
+#[derive(Default)]
+pub struct Progress<'a> {
+    pub callback: Option<Callback<'a>>,
+    pub cancel: Option<CancelToken>,
+}
+
+impl<'a> Progress<'a> {
+    pub fn report(&mut self, phase: Phase, done: u64, total: u64) -> Result<(), Error> {
+        if self.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(Error::Cancelled);
+        }
+        if let Some(callback) = self.callback.as_mut() {
+            callback(phase, done, total);
+        }
+        Ok(())
+    }
+}
+
+// 按字符边界把文本切成大约 CHUNK_SIZE 字节的块
+pub fn chunks(text: &str) -> impl Iterator<Item = &str> {
+    chunks_of(text, CHUNK_SIZE)
+}
+
+// 按字符边界把文本切成大约 size 字节的块
+pub fn chunks_of(text: &str, size: usize) -> impl Iterator<Item = &str> {
+    let size = size.max(1);
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = rest.len().min(size);
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+// SIGINT 处理函数里只能访问静态变量，这里保存命令行的取消令牌
+static INTERRUPT_TOKEN: OnceLock<CancelToken> = OnceLock::new();
+
+// 返回一个按 Ctrl-C 时被取消的令牌；其它平台上只是一个普通的令牌
+pub fn cancel_on_interrupt() -> CancelToken {
+    let token = INTERRUPT_TOKEN.get_or_init(CancelToken::new).clone();
+    #[cfg(unix)]
+    {
+        extern "C" fn on_interrupt(_: std::os::raw::c_int) {
+            if let Some(token) = INTERRUPT_TOKEN.get() {
+                token.cancel();
+            }
+        }
+        extern "C" {
+            fn signal(signum: std::os::raw::c_int, handler: extern "C" fn(std::os::raw::c_int)) -> usize;
+        }
+        const SIGINT: std::os::raw::c_int = 2;
+        unsafe {
+            signal(SIGINT, on_interrupt);
+        }
+    }
+    token
+}
+
+const BAR_WIDTH: usize = 30;
+
+// 标准错误是终端时返回画进度条的 Progress，否则返回不做任何事的
+pub fn terminal_bar<'a>() -> Progress<'a> {
+    if !io::stderr().is_terminal() {
+        return Progress::default();
+    }
+    let mut last = None;
+    Progress {
+        callback: Some(Box::new(move |phase: Phase, done: u64, total: u64| {
+            let percent = (done.min(total) * 100).checked_div(total).unwrap_or(100) as usize;
+            // 百分比和阶段都没变就不重画
+            if last == Some((phase, percent)) {
+                return;
+            }
+            last = Some((phase, percent));
+            let filled = percent * BAR_WIDTH / 100;
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r[{}{}] {:>3}% {:<8} {:.1}/{:.1} MB", "#".repeat(filled), " ".repeat(BAR_WIDTH - filled),
+                percent, phase.name(), done as f64 / 1e6, total as f64 / 1e6);
+            if phase == Phase::Writing && percent == 100 {
+                let _ = writeln!(stderr);
+            }
+            let _ = stderr.flush();
+        })),
+        cancel: None,
+    }
+}
@@ -0,0 +1,62 @@
+// 游程编码预处理，类似 bzip2 的 RLE1：
+// 同一个字符连续出现 RLE_MIN_RUN 次之后，紧跟一个计数字符，
+// 它的码点就是额外重复的次数。这样长串的重复字符只占 5 个符号。
+
+use super::Error;
+
+const RLE_MIN_RUN: usize = 4;
+// 计数字符的码点不能进入代理区，否则不是合法的 char
+const RLE_MAX_EXTRA: usize = 0xD7FF;
+
+pub fn encode(contents: &str) -> String {
+    let chars: Vec<char> = contents.chars().collect();
+    let mut out = String::with_capacity(contents.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let mut run = 1;
+        while i + run < chars.len() && chars[i + run] == ch && run < RLE_MIN_RUN + RLE_MAX_EXTRA {
+            run += 1;
+        }
+        if run >= RLE_MIN_RUN {
+            for _ in 0..RLE_MIN_RUN {
+                out.push(ch);
+            }
+            out.push(char::from_u32((run - RLE_MIN_RUN) as u32).unwrap());
+        } else {
+            for _ in 0..run {
+                out.push(ch);
+            }
+        }
+        i += run;
+    }
+    out
+}
+
+pub fn decode(contents: &str) -> Result<String, Error> {
+    let mut out = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    let mut last = None;
+    let mut run = 0;
+    while let Some(ch) = chars.next() {
+        out.push(ch);
+        if Some(ch) == last {
+            run += 1;
+        } else {
+            last = Some(ch);
+            run = 1;
+        }
+        if run == RLE_MIN_RUN {
+            let extra = chars.next().ok_or(Error::Corrupt("Truncated RLE run"))? as usize;
+            if extra > RLE_MAX_EXTRA {
+                return Err(Error::Corrupt("Invalid RLE count"));
+            }
+            for _ in 0..extra {
+                out.push(ch);
+            }
+            last = None;
+            run = 0;
+        }
+    }
+    Ok(out)
+}
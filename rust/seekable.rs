@@ -0,0 +1,220 @@
+// 可随机访问的压缩格式（compress --seekable）。输入按字节切成固定大小的块，每块单独编码，
+// 末尾是块索引，decompress --range START:LEN 只解码和这个范围相交的块。
+// 按字节编码（每个字节当作码点 0..=255 的字符），这样块的边界和范围都是字节偏移，
+// 不会切开 UTF-8 字符。编码表单独存放，每块在索引里引用一张表：
+// 默认整个文件共用一张表，--block-tables 时每块一张。
+//
+// 格式: SEEK_MAGIC | VERSION | 表个数(u32) | { 表长度(u32) | 树 }... | 块...
+//       | 索引 { 原始偏移(u64) | 压缩偏移(u64) | 表编号(u32) }... | 索引偏移(u64) | 块个数(u32) | 原始总长度(u64) | SEEK_MAGIC
+// 每块: 比特数(u64) | 数据
+// 建树和编解码用的是 no_std 的核心（huffman.rs），这里只负责格式，树按 byoct1.rs 的格式序列化。
+// 核心的码表是定长数组，块很小、表很多时不能全留在内存里：压缩时建完一张表马上序列化，解压时用到才解析。
+
+use super::huffman::{self, Codes};
+use super::progress::{Phase, Progress};
+use super::{count, serialize_tree};
+use super::{Error, HuffmanNode, VERSION};
+
+pub const SEEK_MAGIC: &[u8; 4] = b"BYCS";
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+const TRAILER_LEN: usize = 8 + 4 + 8 + 4;
+const INDEX_ENTRY_LEN: usize = 8 + 8 + 4;
+
+struct Block {
+    offset: u64,
+    compressed_offset: u64,
+    table: u32,
+}
+
+// (每张编码表序列化的树, 块索引, 原始总长度, 索引的位置)
+type Index<'a> = (Vec<&'a [u8]>, Vec<Block>, u64, usize);
+
+pub fn is_seekable(bytes: &[u8]) -> bool {
+    bytes.starts_with(SEEK_MAGIC)
+}
+
+pub fn compress(contents: &[u8], block_size: usize, table_per_block: bool, progress: &mut Progress) -> Result<Vec<u8>, Error> {
+    let total = contents.len() as u64;
+    let block_size = block_size.max(1);
+    let blocks: Vec<&[u8]> = contents.chunks(block_size).collect();
+
+    // 共用的表用整个文件的频率，每块一张表时用各自的频率
+    progress.report(Phase::Counting, 0, total)?;
+    let threads = count::threads();
+    let shared = if table_per_block || blocks.is_empty() {
+        None
+    } else {
+        let histogram = *count::count_bytes(contents, threads).bytes();
+        progress.report(Phase::Building, 0, total)?;
+        Some(Box::new(Codes::from_counts(&histogram)?))
+    };
+
+    let mut trees = Vec::new();
+    if let Some(codes) = &shared {
+        trees.push(serialize_tree(&HuffmanNode::from_codes(codes, codes.root())));
+    }
+    let mut encoded = Vec::new();
+    let mut offset = 0;
+    for block in blocks.iter() {
+        let (bits, bit_len) = match &shared {
+            Some(codes) => huffman::encode(block, codes)?,
+            None => {
+                let codes = Box::new(Codes::from_counts(count::count_bytes(block, threads).bytes())?);
+                trees.push(serialize_tree(&HuffmanNode::from_codes(&codes, codes.root())));
+                huffman::encode(block, &codes)?
+            },
+        };
+        encoded.push((bits, bit_len));
+        offset += block.len() as u64;
+        progress.report(Phase::Encoding, offset, total)?;
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(SEEK_MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&(trees.len() as u32).to_le_bytes());
+    for tree in trees.iter() {
+        out.extend_from_slice(&(tree.len() as u32).to_le_bytes());
+        out.extend_from_slice(tree);
+    }
+
+    let mut index = Vec::new();
+    let mut offset = 0;
+    for (i, ((bits, bit_len), block)) in encoded.iter().zip(blocks.iter()).enumerate() {
+        let table = if table_per_block { i } else { 0 };
+        index.push(Block { offset, compressed_offset: out.len() as u64, table: table as u32 });
+        out.extend_from_slice(&(*bit_len as u64).to_le_bytes());
+        out.extend_from_slice(bits);
+        offset += block.len() as u64;
+    }
+
+    progress.report(Phase::Writing, 0, total)?;
+    let index_offset = out.len() as u64;
+    for block in index.iter() {
+        out.extend_from_slice(&block.offset.to_le_bytes());
+        out.extend_from_slice(&block.compressed_offset.to_le_bytes());
+        out.extend_from_slice(&block.table.to_le_bytes());
+    }
+    out.extend_from_slice(&index_offset.to_le_bytes());
+    out.extend_from_slice(&(index.len() as u32).to_le_bytes());
+    out.extend_from_slice(&total.to_le_bytes());
+    out.extend_from_slice(SEEK_MAGIC);
+    progress.report(Phase::Writing, total, total)?;
+    Ok(out)
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Result<u32, Error> {
+    let value = bytes.get(pos..pos.checked_add(4).ok_or(Error::Corrupt("Truncated index"))?).ok_or(Error::Corrupt("Truncated index"))?;
+    Ok(u32::from_le_bytes(value.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &[u8], pos: usize) -> Result<u64, Error> {
+    let value = bytes.get(pos..pos.checked_add(8).ok_or(Error::Corrupt("Truncated index"))?).ok_or(Error::Corrupt("Truncated index"))?;
+    Ok(u64::from_le_bytes(value.try_into().unwrap()))
+}
+
+fn read_index(bytes: &[u8]) -> Result<Index<'_>, Error> {
+    if bytes.len() < SEEK_MAGIC.len() + 1 + 4 + TRAILER_LEN || !is_seekable(bytes) || !bytes.ends_with(SEEK_MAGIC) {
+        return Err(Error::Format("Not a seekable file"));
+    }
+    if bytes[SEEK_MAGIC.len()] != VERSION {
+        return Err(Error::UnsupportedVersion(bytes[SEEK_MAGIC.len()]));
+    }
+    let trailer = bytes.len() - TRAILER_LEN;
+    let index_offset = usize::try_from(read_u64(bytes, trailer)?).map_err(|_| Error::Corrupt("Invalid index offset"))?;
+    let count = read_u32(bytes, trailer + 8)? as usize;
+    let total = read_u64(bytes, trailer + 12)?;
+    if index_offset > trailer || (trailer - index_offset) / INDEX_ENTRY_LEN != count || !(trailer - index_offset).is_multiple_of(INDEX_ENTRY_LEN) {
+        return Err(Error::Corrupt("Invalid index"));
+    }
+
+    let mut pos = SEEK_MAGIC.len() + 1;
+    let table_count = read_u32(bytes, pos)? as usize;
+    pos += 4;
+    let mut tables = Vec::new();
+    for _ in 0..table_count {
+        let len = read_u32(bytes, pos)? as usize;
+        pos += 4;
+        let tree = bytes.get(pos..pos + len).filter(|_| pos + len <= index_offset).ok_or(Error::Corrupt("Truncated table"))?;
+        tables.push(tree);
+        pos += len;
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for i in 0..count {
+        let entry = index_offset + i * INDEX_ENTRY_LEN;
+        let block = Block { offset: read_u64(bytes, entry)?, compressed_offset: read_u64(bytes, entry + 8)?, table: read_u32(bytes, entry + 16)? };
+        // 偏移必须递增，块数据在表之后、索引之前，表编号要存在
+        let previous = blocks.last();
+        if previous.is_some_and(|previous| block.offset < previous.offset || block.compressed_offset < previous.compressed_offset)
+            || block.offset > total || block.compressed_offset < pos as u64 || block.compressed_offset > index_offset as u64
+            || block.table as usize >= tables.len() {
+            return Err(Error::Corrupt("Invalid index"));
+        }
+        blocks.push(block);
+    }
+    Ok((tables, blocks, total, index_offset))
+}
+
+// 解码第 i 块。cached 是上一块用的表，共用一张表时只解析一次
+fn decode_block(bytes: &[u8], tables: &[&[u8]], cached: &mut Option<(u32, Box<Codes>)>, blocks: &[Block], i: usize, index_offset: usize, total: u64) -> Result<Vec<u8>, Error> {
+    let block = &blocks[i];
+    if cached.as_ref().is_none_or(|(table, _)| *table != block.table) {
+        let codes = HuffmanNode::deserialize(tables[block.table as usize])?.to_codes()?;
+        *cached = Some((block.table, Box::new(codes)));
+    }
+    let codes = &cached.as_ref().unwrap().1;
+    let start = block.compressed_offset as usize;
+    let end = blocks.get(i + 1).map_or(index_offset, |next| next.compressed_offset as usize);
+    let expected_len = blocks.get(i + 1).map_or(total, |next| next.offset) - block.offset;
+    let bit_len = usize::try_from(read_u64(bytes, start)?).map_err(|_| Error::Corrupt("Invalid block"))?;
+    let data = bytes.get(start + 8..end).ok_or(Error::Corrupt("Truncated block"))?;
+    // 比特数先和块的实际数据比较，分配的输出就不会超过 data.len() * 8 字节，和 huffman::decode 一样
+    if bit_len > data.len() * 8 {
+        return Err(Error::Corrupt("Truncated block"));
+    }
+    // 每个字节至少占一个比特，索引里的长度超过比特数一定是损坏的
+    if expected_len > bit_len as u64 {
+        return Err(Error::Corrupt("Block length does not match index"));
+    }
+    let mut decoded = vec![0; expected_len as usize];
+    let written = huffman::decode_into(data, bit_len, codes, &mut decoded)?;
+    if written != decoded.len() {
+        return Err(Error::Corrupt("Block length does not match index"));
+    }
+    Ok(decoded)
+}
+
+pub fn decompress(bytes: &[u8], progress: &mut Progress) -> Result<Vec<u8>, Error> {
+    let (_, _, total, _) = read_index(bytes)?;
+    decompress_range(bytes, 0, total, progress)
+}
+
+// 只解码和 [start, start + len) 相交的块，超出文件末尾的部分忽略
+pub fn decompress_range(bytes: &[u8], start: u64, len: u64, progress: &mut Progress) -> Result<Vec<u8>, Error> {
+    let (tables, blocks, total, index_offset) = read_index(bytes)?;
+    let start = start.min(total);
+    let end = start.saturating_add(len).min(total);
+    // 每个字节至少占一个比特，损坏的总长度不会让这里分配过多的内存
+    let mut out = Vec::with_capacity((end - start).min(bytes.len() as u64 * 8) as usize);
+    // 第一个相交的块是最后一个起点不超过 start 的块
+    let first = blocks.partition_point(|block| block.offset <= start).saturating_sub(1);
+    let mut cached = None;
+    for i in first..blocks.len() {
+        let block_start = blocks[i].offset;
+        if block_start >= end {
+            break;
+        }
+        progress.report(Phase::Decoding, block_start - start.min(block_start), end - start)?;
+        let decoded = decode_block(bytes, &tables, &mut cached, &blocks, i, index_offset, total)?;
+        let from = (start.saturating_sub(block_start) as usize).min(decoded.len());
+        let to = ((end - block_start) as usize).min(decoded.len());
+        out.extend_from_slice(&decoded[from..to]);
+    }
+    // 索引里的块没有覆盖整个范围（比如第一块不从 0 开始）时输出会变短
+    if out.len() as u64 != end - start {
+        return Err(Error::Corrupt("Decoded length does not match index"));
+    }
+    progress.report(Phase::Writing, end - start, end - start)?;
+    Ok(out)
+}
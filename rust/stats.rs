@@ -0,0 +1,145 @@
+// byoct stats <file> [--bytes] [--json|--csv]
+// 统计整个文件（包括换行）的字符或字节频率，输出熵、Huffman 平均码长和预计压缩后的大小。
+// 不是合法 UTF-8 的文件自动按字节统计。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{build_hashmap, build_huffman_codes_recursive, build_huffman_tree, error, serialize_tree, Error, Metadata};
+
+// 文件头里除了树和元数据以外的固定部分：MAGIC | VERSION | flags | 树长度 | 比特数
+const HEADER_LEN: usize = 4 + 1 + 1 + 4 + 8;
+
+pub fn run(args: &[String]) {
+    let mut bytes_mode = false;
+    let mut format = "text";
+    let mut input_file = None;
+    for arg in args {
+        match arg.as_str() {
+            "--bytes" => bytes_mode = true,
+            "--json" => format = "json",
+            "--csv" => format = "csv",
+            _ => input_file = Some(arg),
+        }
+    }
+    let input_file = match input_file {
+        Some(input_file) => input_file,
+        None => {
+            println!("Usage: byoct stats [--bytes] [--json|--csv] <input_file>");
+            std::process::exit(1);
+        }
+    };
+
+    let contents = fs::read(input_file)
+        .unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", input_file), e)));
+    // 按字节统计时每个字节当作码点 0..=255 的字符，后面的建树代码不用改
+    let text = match std::str::from_utf8(&contents) {
+        Ok(text) if !bytes_mode => text.to_string(),
+        _ => {
+            bytes_mode = true;
+            contents.iter().map(|&byte| char::from(byte)).collect()
+        },
+    };
+
+    // 压缩文件时还会写元数据：文件名、权限、修改时间和原始长度
+    let mut metadata = Vec::new();
+    Metadata { size: Some(contents.len() as u64), ..Metadata::from_file(Path::new(input_file), true).unwrap_or_default() }.serialize(&mut metadata);
+    let header_len = HEADER_LEN + metadata.len();
+
+    let hashmap = build_hashmap(&text);
+    let total: usize = hashmap.values().sum();
+    let mut histogram: Vec<(char, usize)> = hashmap.iter().map(|(ch, freq)| (*ch, *freq)).collect();
+    histogram.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut entropy = 0.0;
+    for (_, freq) in histogram.iter() {
+        let p = *freq as f64 / total as f64;
+        entropy -= p * p.log2();
+    }
+
+    let mut codes = HashMap::new();
+    let mut tree_len = 0;
+    if !hashmap.is_empty() {
+        let root = build_huffman_tree(&hashmap);
+        build_huffman_codes_recursive(&root, &mut codes, &mut String::new());
+        tree_len = serialize_tree(&root).len();
+    }
+    let payload_bits: usize = histogram.iter().map(|(ch, freq)| freq * codes[ch].len()).sum();
+    let average_code_length = if total > 0 { payload_bits as f64 / total as f64 } else { 0.0 };
+    let compressed_size = header_len + tree_len + payload_bits.div_ceil(8);
+
+    let symbol = |ch: char| escape_symbol(ch, bytes_mode);
+    // JSON 和 CSV 里放原始字符，由各自的格式负责转义
+    let raw_symbol = |ch: char| if bytes_mode { format!("0x{:02x}", ch as u32) } else { ch.to_string() };
+    match format {
+        "json" => {
+            println!("{{");
+            println!("  \"file\": \"{}\",", escape_json(input_file));
+            println!("  \"unit\": \"{}\",", if bytes_mode { "byte" } else { "char" });
+            println!("  \"total_symbols\": {},", total);
+            println!("  \"distinct_symbols\": {},", histogram.len());
+            println!("  \"entropy_bits_per_symbol\": {:.6},", entropy);
+            println!("  \"average_code_length\": {:.6},", average_code_length);
+            println!("  \"original_size\": {},", contents.len());
+            println!("  \"estimated_payload_size\": {},", payload_bits.div_ceil(8));
+            println!("  \"estimated_compressed_size\": {},", compressed_size);
+            println!("  \"histogram\": [");
+            for (i, (ch, freq)) in histogram.iter().enumerate() {
+                let comma = if i + 1 < histogram.len() { "," } else { "" };
+                println!("    {{\"symbol\": \"{}\", \"count\": {}, \"code_length\": {}}}{}",
+                    escape_json(&raw_symbol(*ch)), freq, codes[ch].len(), comma);
+            }
+            println!("  ]");
+            println!("}}");
+        },
+        "csv" => {
+            println!("symbol,count,probability,code_length");
+            for (ch, freq) in histogram.iter() {
+                println!("\"{}\",{},{:.6},{}", raw_symbol(*ch).replace('"', "\"\""), freq,
+                    *freq as f64 / total as f64, codes[ch].len());
+            }
+        },
+        _ => {
+            println!("File: {}", input_file);
+            println!("Symbols: {} {}s, {} distinct", total, if bytes_mode { "byte" } else { "char" }, histogram.len());
+            println!("Entropy: {:.4} bits/symbol", entropy);
+            println!("Average Huffman code length: {:.4} bits/symbol", average_code_length);
+            println!("Original size: {} bytes", contents.len());
+            println!("Estimated compressed size: {} bytes ({} payload + {} tree + {} header)",
+                compressed_size, payload_bits.div_ceil(8), tree_len, header_len);
+            println!();
+            for (ch, freq) in histogram.iter() {
+                println!("{:>8}  {:>10}  {:>8.4}%  {:>3} bits", symbol(*ch), freq,
+                    *freq as f64 * 100.0 / total as f64, codes[ch].len());
+            }
+        },
+    }
+}
+
+// 不可打印的符号转义显示，按字节统计时显示成十六进制
+pub fn escape_symbol(ch: char, bytes_mode: bool) -> String {
+    if bytes_mode && !(ch as u32 >= 0x21 && ch as u32 <= 0x7e) {
+        format!("0x{:02x}", ch as u32)
+    } else if ch == ' ' {
+        String::from("' '")
+    } else {
+        ch.escape_debug().to_string()
+    }
+}
+
+pub fn escape_json(text: &str) -> String {
+    let mut out = String::new();
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,159 @@
+// byoct train [-o <table>] <sample...>
+// 从一批样本训练共享的编码表（字典模式）。大量很小、相似的消息各自带一棵树时，树比数据本身还大；
+// compress --table 用训练好的表编码，文件头里只记 8 字节的表哈希，decompress --table 核对哈希之后解码。
+// 表里没有的字符和预设表一样，先写转义码，再写 21 位的码点。
+//
+// 表文件格式: TABLE_MAGIC | VERSION | 符号个数(u32) | { 码点(u32) | 频率(u32) }...
+// 符号按码点排序，同样的样本总是得到同样的表文件。哈希是整个表文件的 FNV-1a 64 位哈希。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::presets::ESCAPE;
+use super::{build_hashmap, build_huffman_tree, error, Error, HuffmanNode, VERSION};
+
+const TABLE_MAGIC: &[u8; 4] = b"BYTB";
+const HEADER_LEN: usize = 4 + 1 + 4;
+const ENTRY_LEN: usize = 4 + 4;
+
+pub struct Table {
+    pub hash: u64,
+    pub root: Box<HuffmanNode>,
+}
+
+impl Table {
+    pub fn load(path: &Path) -> Result<Table, Error> {
+        let bytes = fs::read(path).map_err(|e| Error::Io(format!("Error reading {}", path.display()), e))?;
+        Table::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Table, Error> {
+        if bytes.len() < HEADER_LEN || !bytes.starts_with(TABLE_MAGIC) {
+            return Err(Error::Format("Not a code table"));
+        }
+        if bytes[TABLE_MAGIC.len()] != VERSION {
+            return Err(Error::UnsupportedVersion(bytes[TABLE_MAGIC.len()]));
+        }
+        let count = u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize;
+        let entries = &bytes[HEADER_LEN..];
+        if !entries.len().is_multiple_of(ENTRY_LEN) || entries.len() / ENTRY_LEN != count {
+            return Err(Error::Corrupt("Truncated table"));
+        }
+        // 码点不重复，频率不超过 u32，建树时频率相加不会溢出
+        let mut frequencies = HashMap::new();
+        for entry in entries.chunks(ENTRY_LEN) {
+            let ch = char::from_u32(u32::from_le_bytes(entry[..4].try_into().unwrap())).ok_or(Error::Corrupt("Invalid char in table"))?;
+            let freq = u32::from_le_bytes(entry[4..].try_into().unwrap()) as usize;
+            if freq == 0 || frequencies.insert(ch, freq).is_some() {
+                return Err(Error::Corrupt("Invalid table entry"));
+            }
+        }
+        if !frequencies.contains_key(&ESCAPE) {
+            return Err(Error::Corrupt("Table has no escape symbol"));
+        }
+        Ok(Table { hash: hash(bytes), root: build_huffman_tree(&frequencies) })
+    }
+}
+
+// FNV-1a，64 位
+pub fn hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+// 和 compress 一样，不是合法 UTF-8 的样本按字节当作码点 0..=255 的字符
+fn to_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&byte| char::from(byte)).collect(),
+    }
+}
+
+// 返回表文件的内容
+pub fn train(samples: &[String]) -> Vec<u8> {
+    let mut frequencies: HashMap<char, usize> = HashMap::new();
+    for sample in samples {
+        for (ch, freq) in build_hashmap(sample) {
+            *frequencies.entry(ch).or_insert(0) += freq;
+        }
+    }
+    // 样本里真的出现的 ESCAPE 也要走转义。转义的频率按只出现过一次的符号个数估计：
+    // 样本里罕见的符号越多，新消息里遇到没见过的符号的可能越大
+    frequencies.remove(&ESCAPE);
+    let singletons = frequencies.values().filter(|freq| **freq == 1).count();
+    frequencies.insert(ESCAPE, singletons.max(1));
+
+    // 频率存成 u32，语料太大时整体右移，出现过的符号至少保留 1
+    let max = frequencies.values().copied().max().unwrap_or(0);
+    let mut shift = 0;
+    while max >> shift > u32::MAX as usize {
+        shift += 1;
+    }
+    let mut entries: Vec<(char, usize)> = frequencies.into_iter().collect();
+    entries.sort();
+
+    let mut out = Vec::new();
+    out.extend_from_slice(TABLE_MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (ch, freq) in entries {
+        out.extend_from_slice(&(ch as u32).to_le_bytes());
+        out.extend_from_slice(&((freq >> shift).max(1) as u32).to_le_bytes());
+    }
+    out
+}
+
+pub fn run(args: &[String]) {
+    let mut output = String::from("table.bct");
+    let mut paths = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match iter.next() {
+                Some(name) => output = name.clone(),
+                None => error::exit(Error::Usage(String::from("-o needs a file name"))),
+            },
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() {
+        println!("Usage: byoct train [-o <table>] <sample...>");
+        std::process::exit(1);
+    }
+
+    let mut samples = Vec::new();
+    for path in paths {
+        if let Err(e) = collect_samples(Path::new(path), &mut samples) {
+            error::exit(e);
+        }
+    }
+    let table = train(&samples);
+    if let Err(e) = fs::write(&output, &table) {
+        error::exit(Error::Io(format!("Error writing {}", output), e));
+    }
+    println!("{} samples, {} symbols -> {} (hash {:016x})", samples.len(), (table.len() - HEADER_LEN) / ENTRY_LEN, output, hash(&table));
+}
+
+// 目录按文件名排序递归展开，每个文件是一个样本
+fn collect_samples(path: &Path, samples: &mut Vec<String>) -> Result<(), Error> {
+    if path.is_dir() {
+        let mut children: Vec<_> = fs::read_dir(path)
+            .map_err(|e| Error::Io(format!("Error reading {}", path.display()), e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        children.sort();
+        for child in children {
+            collect_samples(&child, samples)?;
+        }
+        return Ok(());
+    }
+    let bytes = fs::read(path).map_err(|e| Error::Io(format!("Error reading {}", path.display()), e))?;
+    samples.push(to_text(&bytes));
+    Ok(())
+}
@@ -0,0 +1,255 @@
+// C 接口的测试程序，由 capi.rs 编译链接之后在 rust/test 目录下运行。
+// 检查一次性和流式的压缩、解压，缓冲区不够和各种错误，再和 corpus/v1 里的金样本逐字节比较。
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "byoct.h"
+
+static int failures = 0;
+
+#define CHECK(cond, ...) do { \
+    if (!(cond)) { \
+        printf("FAIL  %s:%d: ", __func__, __LINE__); \
+        printf(__VA_ARGS__); \
+        printf("\n"); \
+        failures++; \
+        return; \
+    } \
+} while (0)
+
+// 读整个文件，失败时返回 NULL
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *file = fopen(path, "rb");
+    if (!file) {
+        return NULL;
+    }
+    size_t cap = 4096;
+    uint8_t *data = malloc(cap);
+    *len = 0;
+    size_t n;
+    while ((n = fread(data + *len, 1, cap - *len, file)) > 0) {
+        *len += n;
+        if (*len == cap) {
+            cap *= 2;
+            data = realloc(data, cap);
+        }
+    }
+    fclose(file);
+    return data;
+}
+
+// 压缩再解压，检查得到原来的输入，压缩后的长度不超过 byoct_compress_bound
+static void roundtrip(const char *name, const uint8_t *input, size_t len) {
+    size_t bound = byoct_compress_bound(len);
+    uint8_t *compressed = malloc(bound);
+    size_t compressed_len = 0;
+    int rc = byoct_compress(input, len, compressed, bound, &compressed_len);
+    CHECK(rc == BYOCT_OK, "%s: compress: %s", name, byoct_error_string(rc));
+    CHECK(compressed_len <= bound, "%s: %zu bytes exceed the bound %zu", name, compressed_len, bound);
+
+    uint8_t *decoded = malloc(len + 1);
+    size_t decoded_len = 0;
+    rc = byoct_decompress(compressed, compressed_len, decoded, len + 1, &decoded_len);
+    CHECK(rc == BYOCT_OK, "%s: decompress: %s", name, byoct_error_string(rc));
+    CHECK(decoded_len == len && memcmp(decoded, input, len) == 0, "%s: decoded data differs", name);
+    free(compressed);
+    free(decoded);
+}
+
+static uint64_t state = 0x9e3779b97f4a7c15;
+
+static uint64_t next_random(void) {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    return state;
+}
+
+static void test_roundtrip(void) {
+    roundtrip("empty", NULL, 0);
+    roundtrip("abracadabra", (const uint8_t *)"abracadabra", 11);
+    roundtrip("single", (const uint8_t *)"zzzzzzzz", 8);
+    const char *unicode = "Huffman 编码 \xe2\x9c\x93 \xf0\x9f\x98\x80 \"quoted\" \\ back";
+    roundtrip("unicode", (const uint8_t *)unicode, strlen(unicode));
+
+    uint8_t bytes[256];
+    for (int i = 0; i < 256; i++) {
+        bytes[i] = (uint8_t)i;
+    }
+    roundtrip("all bytes once", bytes, sizeof bytes);
+
+    size_t len = 1 << 20;
+    uint8_t *random = malloc(len);
+    for (size_t i = 0; i < len; i++) {
+        random[i] = (uint8_t)next_random();
+    }
+    roundtrip("random bytes", random, len);
+    // 偏斜的分布
+    for (size_t i = 0; i < len; i++) {
+        random[i] = (uint8_t)(__builtin_ctzll(next_random() | 1ull << 40) + 'a');
+    }
+    roundtrip("skewed bytes", random, len);
+    free(random);
+}
+
+static void test_buffer_too_small(void) {
+    const uint8_t *input = (const uint8_t *)"abracadabra";
+    uint8_t compressed[1024];
+    size_t needed = 0;
+    int rc = byoct_compress(input, 11, compressed, 10, &needed);
+    CHECK(rc == BYOCT_ERROR_BUFFER_TOO_SMALL, "compress into 10 bytes: %s", byoct_error_string(rc));
+    size_t compressed_len = 0;
+    rc = byoct_compress(input, 11, compressed, needed, &compressed_len);
+    CHECK(rc == BYOCT_OK && compressed_len == needed, "compress into %zu bytes: %s", needed, byoct_error_string(rc));
+
+    // 只问需要多长
+    rc = byoct_decompress(compressed, compressed_len, NULL, 0, &needed);
+    CHECK(rc == BYOCT_ERROR_BUFFER_TOO_SMALL && needed == 11, "decompress without a buffer: %s, %zu", byoct_error_string(rc), needed);
+}
+
+static void test_errors(void) {
+    uint8_t out[64];
+    size_t out_len = 0;
+    int rc = byoct_decompress((const uint8_t *)"not compressed", 14, out, sizeof out, &out_len);
+    CHECK(rc == BYOCT_ERROR_FORMAT, "garbage: %s", byoct_error_string(rc));
+
+    uint8_t compressed[1024];
+    size_t compressed_len = 0;
+    byoct_compress((const uint8_t *)"abracadabra", 11, compressed, sizeof compressed, &compressed_len);
+    rc = byoct_decompress(compressed, compressed_len - 1, out, sizeof out, &out_len);
+    CHECK(rc == BYOCT_ERROR_CORRUPT, "truncated: %s", byoct_error_string(rc));
+    compressed[4] = 99;
+    rc = byoct_decompress(compressed, compressed_len, out, sizeof out, &out_len);
+    CHECK(rc == BYOCT_ERROR_UNSUPPORTED, "future version: %s", byoct_error_string(rc));
+
+    CHECK(byoct_compress(NULL, 1, out, sizeof out, &out_len) == BYOCT_ERROR_ARGUMENT, "NULL input");
+    CHECK(byoct_compress((const uint8_t *)"a", 1, out, sizeof out, NULL) == BYOCT_ERROR_ARGUMENT, "NULL length");
+    CHECK(byoct_compress((const uint8_t *)"a", 1, NULL, 8, &out_len) == BYOCT_ERROR_ARGUMENT, "NULL output");
+
+    for (int code = BYOCT_OK; code <= BYOCT_ERROR_INTERNAL; code++) {
+        CHECK(strcmp(byoct_error_string(code), byoct_error_string(-1)) != 0, "no message for %d", code);
+    }
+    CHECK(strcmp(byoct_error_string(BYOCT_ERROR_CORRUPT), "Corrupt data") == 0, "wrong message");
+}
+
+// 按 chunk 字节一次写进编码器，按 chunk 字节一次读出来，和一次性压缩的结果一样
+static void test_stream(void) {
+    const char *text = "It was the best of times, it was the worst of times, it was the age of wisdom";
+    size_t len = strlen(text);
+    uint8_t expected[4096];
+    size_t expected_len = 0;
+    byoct_compress((const uint8_t *)text, len, expected, sizeof expected, &expected_len);
+
+    byoct_encoder *encoder = byoct_encoder_new();
+    uint8_t out[4096];
+    size_t n = 0;
+    CHECK(byoct_encoder_read(encoder, out, sizeof out, &n) == BYOCT_ERROR_ARGUMENT, "read before finish");
+    for (size_t pos = 0; pos < len; pos += 7) {
+        size_t chunk = len - pos < 7 ? len - pos : 7;
+        CHECK(byoct_encoder_write(encoder, (const uint8_t *)text + pos, chunk) == BYOCT_OK, "write");
+    }
+    CHECK(byoct_encoder_finish(encoder) == BYOCT_OK, "finish");
+    CHECK(byoct_encoder_write(encoder, (const uint8_t *)"x", 1) == BYOCT_ERROR_ARGUMENT, "write after finish");
+    CHECK(byoct_encoder_finish(encoder) == BYOCT_ERROR_ARGUMENT, "finish twice");
+    size_t out_len = 0;
+    do {
+        CHECK(byoct_encoder_read(encoder, out + out_len, 5, &n) == BYOCT_OK, "read");
+        out_len += n;
+    } while (n > 0);
+    byoct_encoder_free(encoder);
+    CHECK(out_len == expected_len && memcmp(out, expected, out_len) == 0, "stream output differs from byoct_compress");
+
+    byoct_decoder *decoder = byoct_decoder_new();
+    CHECK(byoct_decoder_write(decoder, out, 10) == BYOCT_OK, "write");
+    // 数据不完整时 finish 失败，接着写完可以再 finish
+    CHECK(byoct_decoder_finish(decoder) == BYOCT_ERROR_CORRUPT, "finish on a truncated stream");
+    CHECK(byoct_decoder_write(decoder, out + 10, out_len - 10) == BYOCT_OK, "write");
+    CHECK(byoct_decoder_finish(decoder) == BYOCT_OK, "finish");
+    uint8_t decoded[4096];
+    size_t decoded_len = 0;
+    do {
+        CHECK(byoct_decoder_read(decoder, decoded + decoded_len, 3, &n) == BYOCT_OK, "read");
+        decoded_len += n;
+    } while (n > 0);
+    byoct_decoder_free(decoder);
+    CHECK(decoded_len == len && memcmp(decoded, text, len) == 0, "stream decoded data differs");
+
+    byoct_encoder_free(NULL);
+    byoct_decoder_free(NULL);
+    CHECK(byoct_encoder_write(NULL, out, 1) == BYOCT_ERROR_ARGUMENT, "NULL encoder");
+    CHECK(byoct_decoder_finish(NULL) == BYOCT_ERROR_ARGUMENT, "NULL decoder");
+}
+
+// MANIFEST 里的每个金样本都能解压成原来的输入，不带选项的压缩结果逐字节一样；
+// 用 --table 压缩的要报 BYOCT_ERROR_MISSING_TABLE
+static void test_corpus(void) {
+    FILE *manifest = fopen("corpus/v1/MANIFEST", "r");
+    CHECK(manifest, "cannot open corpus/v1/MANIFEST, run from rust/test");
+    char line[512];
+    int cases = 0;
+    while (fgets(line, sizeof line, manifest)) {
+        char input_name[128], expected_name[128], options[128] = "";
+        if (line[0] == '#' || sscanf(line, "%127s %127s %127[^\n]", input_name, expected_name, options) < 2) {
+            continue;
+        }
+        char path[300];
+        size_t input_len, expected_len;
+        snprintf(path, sizeof path, "corpus/v1/%s", input_name);
+        uint8_t *input = read_file(path, &input_len);
+        snprintf(path, sizeof path, "corpus/v1/%s", expected_name);
+        uint8_t *expected = read_file(path, &expected_len);
+        CHECK(input && expected, "cannot read %s or %s", input_name, expected_name);
+
+        size_t out_cap = input_len + 1;
+        uint8_t *out = malloc(out_cap);
+        size_t out_len = 0;
+        int rc = byoct_decompress(expected, expected_len, out, out_cap, &out_len);
+        if (strstr(options, "--table")) {
+            CHECK(rc == BYOCT_ERROR_MISSING_TABLE, "%s: %s", expected_name, byoct_error_string(rc));
+        } else {
+            CHECK(rc == BYOCT_OK, "%s: %s", expected_name, byoct_error_string(rc));
+            CHECK(out_len == input_len && memcmp(out, input, input_len) == 0, "%s: decoded data differs", expected_name);
+        }
+        free(out);
+
+        if (options[0] == '\0') {
+            out_cap = byoct_compress_bound(input_len);
+            out = malloc(out_cap);
+            rc = byoct_compress(input, input_len, out, out_cap, &out_len);
+            CHECK(rc == BYOCT_OK, "%s: %s", input_name, byoct_error_string(rc));
+            CHECK(out_len == expected_len && memcmp(out, expected, expected_len) == 0, "%s: differs from %s", input_name, expected_name);
+            free(out);
+        }
+        free(input);
+        free(expected);
+        cases++;
+    }
+    fclose(manifest);
+    printf("      %d corpus cases\n", cases);
+}
+
+int main(void) {
+    struct { const char *name; void (*run)(void); } tests[] = {
+        {"roundtrip", test_roundtrip},
+        {"buffer too small", test_buffer_too_small},
+        {"errors", test_errors},
+        {"stream", test_stream},
+        {"corpus", test_corpus},
+    };
+    size_t count = sizeof tests / sizeof tests[0];
+    for (size_t i = 0; i < count; i++) {
+        int before = failures;
+        tests[i].run();
+        if (failures == before) {
+            printf("ok    %s\n", tests[i].name);
+        }
+    }
+    if (failures > 0) {
+        printf("%d of %zu tests failed\n", failures, count);
+        return 1;
+    }
+    printf("All %zu tests passed\n", count);
+    return 0;
+}
@@ -0,0 +1,160 @@
+// C 接口的测试：检查 ../byoct.h 和 ../capi.rs 一致，把 ../libbyoct.rs 编译成静态库和动态库，
+// 用 cc 分别链接 capi.c 并运行，再用 c++ 检查头文件在 C++ 里也能用。
+// 头文件从 capi.rs 生成：文件开头的第一段说明、BYOCT_ 常量、句柄类型和 byoct_ 函数连同紧挨在前面的注释，
+// 签名按 C_TYPES 翻译。
+// 改了 capi.rs 之后用 --write-header 重新生成 byoct.h。
+//
+// 在 rust/test 目录下运行（需要 cc 和 c++）：
+//   rustc --edition 2021 capi.rs && ./capi [--write-header]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+
+// Rust 类型和对应的 C 类型，指针类型的 C 写法以 * 结尾
+const C_TYPES: &[(&str, &str)] = &[
+    ("i32", "int"),
+    ("usize", "size_t"),
+    ("*const u8", "const uint8_t *"),
+    ("*mut u8", "uint8_t *"),
+    ("*mut usize", "size_t *"),
+    ("*const c_char", "const char *"),
+    ("Encoder", "byoct_encoder"),
+    ("Decoder", "byoct_decoder"),
+    ("*mut Encoder", "byoct_encoder *"),
+    ("*mut Decoder", "byoct_decoder *"),
+];
+
+fn c_type(rust: &str) -> String {
+    match C_TYPES.iter().find(|(name, _)| *name == rust) {
+        Some((_, c)) => c.to_string(),
+        None => fail(&format!("No C type for {} in C_TYPES", rust)),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    println!("FAIL {}", message);
+    process::exit(1);
+}
+
+// "name: type" 翻译成 "type name"
+fn c_param(param: &str) -> String {
+    let (name, rust) = param.split_once(':').unwrap_or_else(|| fail(&format!("Cannot parse parameter {}", param)));
+    let c = c_type(rust.trim());
+    if c.ends_with('*') { format!("{}{}", c, name.trim()) } else { format!("{} {}", c, name.trim()) }
+}
+
+// pub [unsafe] extern "C" fn name(params) [-> ret] {
+fn c_function(line: &str) -> String {
+    let signature = line.split_once("fn ").unwrap().1.trim_end_matches('{').trim();
+    let (name, rest) = signature.split_once('(').unwrap();
+    let (params, ret) = rest.split_once(')').unwrap();
+    let params: Vec<String> = params.split(", ").filter(|param| !param.is_empty()).map(c_param).collect();
+    let params = if params.is_empty() { String::from("void") } else { params.join(", ") };
+    let ret = match ret.trim().strip_prefix("->") {
+        Some(ret) => c_type(ret.trim()),
+        None => String::from("void"),
+    };
+    let separator = if ret.ends_with('*') { "" } else { " " };
+    format!("{}{}{}({});", ret, separator, name, params)
+}
+
+fn header(source: &str) -> String {
+    let mut out = String::new();
+    out.push_str("// 由 test/capi.rs 从 capi.rs 生成，不要手改\n");
+    let mut lines = source.lines().peekable();
+    // 文件开头说明的第一段，后面是实现上的说明
+    while let Some(line) = lines.next_if(|line| line.starts_with("//")) {
+        if line == "//" {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    while lines.next_if(|line| line.starts_with("//")).is_some() {}
+    out.push_str("\n#ifndef BYOCT_H\n#define BYOCT_H\n\n#include <stddef.h>\n#include <stdint.h>\n\n");
+    out.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n");
+
+    let mut comment = Vec::new();
+    let mut previous_define = false;
+    for line in lines {
+        if line.starts_with("//") {
+            comment.push(line);
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+        let item = if let Some(constant) = line.strip_prefix("pub const BYOCT_") {
+            let (name, value) = constant.split_once(": i32 = ").unwrap_or_else(|| fail(&format!("Cannot parse {}", line)));
+            Some(format!("#define BYOCT_{} {}", name, value.trim_end_matches(';')))
+        } else if let Some(name) = line.strip_prefix("pub struct ") {
+            let name = c_type(name.split(['(', ' ', ';']).next().unwrap());
+            Some(format!("typedef struct {} {};", name, name))
+        } else if line.contains("extern \"C\" fn byoct_") {
+            Some(c_function(line))
+        } else {
+            None
+        };
+        if let Some(item) = item {
+            let define = item.starts_with("#define");
+            if !(define && previous_define) {
+                out.push('\n');
+            }
+            for comment in comment.iter() {
+                out.push_str(comment);
+                out.push('\n');
+            }
+            out.push_str(&item);
+            out.push('\n');
+            previous_define = define;
+        }
+        comment.clear();
+    }
+    out.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n#endif\n");
+    out
+}
+
+fn run(command: &mut Command) -> String {
+    let output = command.output().unwrap_or_else(|e| fail(&format!("Cannot run {:?}: {}", command, e)));
+    if !output.status.success() {
+        print!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        fail(&format!("{:?} exited with {}", command, output.status));
+    }
+    String::from_utf8_lossy(&output.stdout).into_owned() + &String::from_utf8_lossy(&output.stderr)
+}
+
+fn main() {
+    let write_header = env::args().any(|arg| arg == "--write-header");
+    let source = fs::read_to_string("../capi.rs").unwrap_or_else(|e| fail(&format!("Cannot read ../capi.rs: {}", e)));
+    let header = header(&source);
+    if write_header {
+        fs::write("../byoct.h", &header).unwrap();
+        println!("wrote ../byoct.h");
+    } else if fs::read_to_string("../byoct.h").ok().as_deref() != Some(header.as_str()) {
+        fail("../byoct.h does not match ../capi.rs, run ./capi --write-header");
+    }
+
+    let dir = env::temp_dir().join(format!("byoct-capi-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let out_dir = dir.to_str().unwrap();
+    let build = run(Command::new("rustc").args(["--edition", "2021", "-O", "--crate-type", "cdylib,staticlib", "--print", "native-static-libs", "--out-dir", out_dir, "../libbyoct.rs"]));
+    let native_libs = build.lines()
+        .find_map(|line| line.split_once("native-static-libs: ").map(|(_, libs)| libs.split_whitespace().map(String::from).collect::<Vec<_>>()))
+        .unwrap_or_default();
+
+    let flags = ["-std=c99", "-Wall", "-Wextra", "-Werror", "-pedantic", "-I..", "capi.c"];
+    let static_exe = dir.join("capi_static");
+    run(Command::new("cc").args(flags).arg(dir.join("libbyoct.a")).args(&native_libs).arg("-o").arg(&static_exe));
+    let shared_exe = dir.join("capi_shared");
+    run(Command::new("cc").args(flags).arg(format!("-L{}", out_dir)).arg("-lbyoct").arg(format!("-Wl,-rpath,{}", out_dir)).arg("-o").arg(&shared_exe));
+    run(Command::new("c++").args(["-x", "c++", "-std=c++11", "-Wall", "-Werror", "-fsyntax-only", "../byoct.h"]));
+
+    for exe in [&static_exe, &shared_exe] {
+        println!("{}", Path::new(exe).file_name().unwrap().to_string_lossy());
+        print!("{}", run(&mut Command::new(exe)));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+    println!("C API tests passed with the static and shared library");
+}
@@ -0,0 +1,260 @@
+// 所有建树实现的一致性测试：同样的频率表交给每个实现，检查
+//   1. 带权路径长度等于独立算出来的最优值
+//   2. 编码是前缀码
+//   3. 满足 Kraft 等式 sum(2^-len) == 1
+// byoct1 的内部函数不对外公开，通过 `byoct codes --csv` 的输出来检查。
+//
+// 在 rust/test 目录下运行：
+//   rustc --edition 2021 ../byoct1.rs -o ../byoct1
+//   rustc --edition 2021 conformance.rs && ./conformance [../byoct1]
+
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+use std::env;
+use std::fs;
+use std::process::{self, Command};
+
+mod comate {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_comate.rs");
+
+    pub fn codes(frequencies: &HashMap<char, usize>, _: &str) -> HashMap<char, String> {
+        let bytes = frequencies.iter().map(|(ch, freq)| (*ch as u8, *freq)).collect();
+        let root = build_huffman_tree(&bytes);
+        let mut codes = HashMap::new();
+        build_huffman_codes(&root, "", &mut codes);
+        codes.into_iter().map(|(byte, code)| (char::from(byte), code)).collect()
+    }
+}
+
+mod fittencode {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_fittencode.rs");
+
+    pub fn codes(frequencies: &HashMap<char, usize>, _: &str) -> HashMap<char, String> {
+        let mut codes = HashMap::new();
+        build_codes(build_tree(frequencies), String::new(), &mut codes);
+        codes
+    }
+}
+
+mod gemini {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_gemini.rs");
+
+    pub fn codes(frequencies: &HashMap<char, usize>, _: &str) -> HashMap<char, String> {
+        let symbols = frequencies.iter().map(|(ch, freq)| (*ch, *freq as u32)).collect();
+        let root = build_huffman_tree(&symbols);
+        let mut codes = HashMap::new();
+        generate_huffman_codes(&root, &mut codes);
+        codes.into_iter()
+            .map(|(ch, bits)| (ch, bits.iter().map(|bit| if *bit == 0 { '0' } else { '1' }).collect()))
+            .collect()
+    }
+}
+
+mod kimi {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_kimi.rs");
+
+    pub fn codes(_: &HashMap<char, usize>, text: &str) -> HashMap<char, String> {
+        let root = build_huffman_tree(text);
+        let mut codes = HashMap::new();
+        generate_huffman_codes(&root, &mut codes, String::new());
+        codes
+    }
+}
+
+mod tongyilingma {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_tongyilingma.rs");
+
+    pub fn codes(frequencies: &HashMap<char, usize>, _: &str) -> HashMap<char, String> {
+        let root = build_huffman_tree(frequencies.clone());
+        let mut codes = HashMap::new();
+        generate_huffman_codes(&root, String::new(), &mut codes);
+        codes
+    }
+}
+
+mod yiyan {
+    #![allow(dead_code)]
+    include!("../build_huffman_tree_yiyan.rs");
+
+    // yiyan 版本只有建树，编码在这里生成
+    pub fn codes(frequencies: &HashMap<char, usize>, _: &str) -> HashMap<char, String> {
+        let root = build_huffman_tree(frequencies);
+        let mut codes = HashMap::new();
+        walk(&root, String::new(), &mut codes);
+        codes
+    }
+
+    fn walk(node: &HuffmanNode, code: String, codes: &mut HashMap<char, String>) {
+        if let Some(symbol) = node.symbol {
+            codes.insert(symbol, code);
+            return;
+        }
+        if let Some(left) = &node.left {
+            walk(left, format!("{}0", code), codes);
+        }
+        if let Some(right) = &node.right {
+            walk(right, format!("{}1", code), codes);
+        }
+    }
+}
+
+type Coder = fn(&HashMap<char, usize>, &str) -> HashMap<char, String>;
+
+// 合成字母表从 '!' 开始：gemini 用 '\0' 当内部节点标记，comate 只支持字节
+const FIRST_SYMBOL: u32 = 0x21;
+
+fn histogram(counts: &[usize]) -> HashMap<char, usize> {
+    counts.iter().enumerate().map(|(i, count)| (char::from_u32(FIRST_SYMBOL + i as u32).unwrap(), *count)).collect()
+}
+
+fn histograms() -> Vec<(String, HashMap<char, usize>)> {
+    let mut cases = vec![
+        (String::from("clrs"), HashMap::from([('a', 45), ('b', 13), ('c', 12), ('d', 16), ('e', 9), ('f', 5)])),
+        (String::from("two symbols"), histogram(&[3, 1])),
+        (String::from("uniform 8"), histogram(&[7; 8])),
+        (String::from("uniform 37"), histogram(&[1; 37])),
+        (String::from("ties"), histogram(&[1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8])),
+    ];
+
+    let mut fibonacci = vec![1, 1];
+    while fibonacci.len() < 25 {
+        fibonacci.push(fibonacci[fibonacci.len() - 1] + fibonacci[fibonacci.len() - 2]);
+    }
+    cases.push((String::from("fibonacci 25"), histogram(&fibonacci)));
+
+    let zipf: Vec<usize> = (1..=200).map(|k| 20000 / k).collect();
+    cases.push((String::from("zipf 200"), histogram(&zipf)));
+
+    // 固定种子的随机频率表
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for i in 0..30 {
+        let len = 2 + (next() % 150) as usize;
+        let counts: Vec<usize> = (0..len).map(|_| 1 + (next() % 500) as usize).collect();
+        cases.push((format!("random {}", i), histogram(&counts)));
+    }
+    cases
+}
+
+// 独立计算最优带权路径长度：每次合并两个最小权重，所有内部节点的权重之和就是答案
+fn optimal_cost(frequencies: &HashMap<char, usize>) -> usize {
+    let mut heap: BinaryHeap<Reverse<usize>> = frequencies.values().map(|freq| Reverse(*freq)).collect();
+    let mut cost = 0;
+    while heap.len() > 1 {
+        let Reverse(a) = heap.pop().unwrap();
+        let Reverse(b) = heap.pop().unwrap();
+        cost += a + b;
+        heap.push(Reverse(a + b));
+    }
+    cost
+}
+
+fn check(frequencies: &HashMap<char, usize>, codes: &HashMap<char, String>) -> Result<(), String> {
+    for ch in frequencies.keys() {
+        if !codes.contains_key(ch) {
+            return Err(format!("no code for {:?}", ch));
+        }
+    }
+    if codes.len() != frequencies.len() {
+        return Err(format!("{} codes for {} symbols", codes.len(), frequencies.len()));
+    }
+
+    let cost: usize = frequencies.iter().map(|(ch, freq)| freq * codes[ch].len()).sum();
+    let optimal = optimal_cost(frequencies);
+    if cost != optimal {
+        return Err(format!("weighted path length {} but optimum is {}", cost, optimal));
+    }
+
+    // 排序之后，如果某个编码是别的编码的前缀，它一定紧挨在后者前面
+    let mut sorted: Vec<&String> = codes.values().collect();
+    sorted.sort();
+    for pair in sorted.windows(2) {
+        if pair[1].starts_with(pair[0].as_str()) {
+            return Err(format!("code {} is a prefix of {}", pair[0], pair[1]));
+        }
+    }
+
+    let max_len = sorted.iter().map(|code| code.len()).max().unwrap_or(0);
+    if max_len >= 128 {
+        return Err(format!("code length {} too long to check", max_len));
+    }
+    let kraft: u128 = sorted.iter().map(|code| 1u128 << (max_len - code.len())).sum();
+    if kraft != 1u128 << max_len {
+        return Err(format!("Kraft sum is {}/2^{}, expected 1", kraft, max_len));
+    }
+    Ok(())
+}
+
+// 通过命令行取 byoct1 的编码表
+fn byoct_codes(byoct: &str, text: &str) -> Result<HashMap<char, String>, String> {
+    let path = env::temp_dir().join(format!("byoct_conformance_{}.txt", process::id()));
+    fs::write(&path, text).map_err(|e| e.to_string())?;
+    let output = Command::new(byoct).arg("codes").arg("--csv").arg(&path).output();
+    let _ = fs::remove_file(&path);
+    let output = output.map_err(|e| format!("cannot run {}: {}", byoct, e))?;
+
+    let mut codes = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines().skip(1) {
+        // "symbol",count,code,length,bits；符号里的引号写成两个引号
+        let end = line.rfind("\",").ok_or_else(|| format!("bad line {:?}", line))?;
+        let symbol = line[1..end].replace("\"\"", "\"");
+        let fields: Vec<&str> = line[end + 2..].split(',').collect();
+        let ch = symbol.chars().next().ok_or_else(|| format!("bad line {:?}", line))?;
+        codes.insert(ch, fields[1].to_string());
+    }
+    Ok(codes)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let byoct = args.get(1).cloned().unwrap_or_else(|| String::from("../byoct1"));
+
+    let coders: &[(&str, Coder)] = &[
+        ("comate", comate::codes),
+        ("fittencode", fittencode::codes),
+        ("gemini", gemini::codes),
+        ("kimi", kimi::codes),
+        ("tongyilingma", tongyilingma::codes),
+        ("yiyan", yiyan::codes),
+    ];
+
+    let mut failures = 0;
+    for (name, frequencies) in histograms() {
+        let mut text = String::new();
+        let mut symbols: Vec<_> = frequencies.iter().collect();
+        symbols.sort();
+        for (ch, freq) in symbols {
+            text.extend(std::iter::repeat(*ch).take(*freq));
+        }
+
+        let mut results = vec![(String::from("byoct1"), byoct_codes(&byoct, &text))];
+        for (coder, codes) in coders {
+            results.push((coder.to_string(), Ok(codes(&frequencies, &text))));
+        }
+        for (coder, codes) in results {
+            match codes.and_then(|codes| check(&frequencies, &codes)) {
+                Ok(()) => println!("PASS {:<13} {}", coder, name),
+                Err(e) => {
+                    println!("FAIL {:<13} {}: {}", coder, name, e);
+                    failures += 1;
+                },
+            }
+        }
+    }
+
+    if failures > 0 {
+        println!("{} failures", failures);
+        process::exit(1);
+    }
+    println!("All builders are optimal");
+}
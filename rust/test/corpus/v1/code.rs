@@ -0,0 +1,7 @@
+fn build_hashmap(contents: &str) -> HashMap<char, usize> {
+    let mut frequencies = HashMap::new();
+    for ch in contents.chars() {
+        *frequencies.entry(ch).or_insert(0) += 1;
+    }
+    frequencies
+}
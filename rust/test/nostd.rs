@@ -0,0 +1,228 @@
+// 在 no_std 环境里测试 huffman.rs：程序本身是 #![no_std]，不链接 std，只用 libc 的 malloc/free/write，
+// 能编译通过就说明核心没有用到 std。检查建树、编解码、码表重建和各种错误。
+//
+// 在 rust/test 目录下运行：
+//   rustc --edition 2021 -C panic=abort nostd.rs && ./nostd
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[path = "../huffman.rs"]
+#[allow(dead_code)]
+mod huffman;
+
+use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt::{self, Write};
+
+use huffman::{Codes, CoreError};
+
+#[link(name = "c")]
+extern "C" {
+    fn malloc(size: usize) -> *mut u8;
+    fn aligned_alloc(align: usize, size: usize) -> *mut u8;
+    fn free(ptr: *mut u8);
+    fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+    fn abort() -> !;
+}
+
+struct Malloc;
+
+unsafe impl GlobalAlloc for Malloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.align() <= 16 {
+            malloc(layout.size())
+        } else {
+            aligned_alloc(layout.align(), layout.size().next_multiple_of(layout.align()))
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _: Layout) {
+        free(ptr)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Malloc = Malloc;
+
+struct Stdout;
+
+impl Write for Stdout {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            let n = unsafe { write(1, bytes.as_ptr(), bytes.len()) };
+            if n <= 0 {
+                return Err(fmt::Error);
+            }
+            bytes = &bytes[n as usize..];
+        }
+        Ok(())
+    }
+}
+
+macro_rules! println {
+    ($($arg:tt)*) => {{
+        let _ = writeln!(Stdout, $($arg)*);
+    }};
+}
+
+// 预编译的 liballoc 带着展开信息，会引用这个符号；panic 直接 abort，不会真的调用它
+#[no_mangle]
+extern "C" fn rust_eh_personality() {}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    println!("panic: {}", info);
+    unsafe { abort() }
+}
+
+fn codes_for(input: &[u8]) -> Result<Codes, CoreError> {
+    let mut counts = [0; huffman::SYMBOLS];
+    huffman::count(input, &mut counts);
+    Codes::from_counts(&counts)
+}
+
+// 编码再解码，检查得到原来的输入，返回比特数
+fn roundtrip(input: &[u8]) -> Result<usize, &'static str> {
+    let codes = codes_for(input).map_err(|_| "build failed")?;
+    let (bits, bit_len) = huffman::encode(input, &codes).map_err(|_| "encode failed")?;
+    if bits.len() != bit_len.div_ceil(8) {
+        return Err("wrong packed length");
+    }
+    let decoded = huffman::decode(&bits, bit_len, &codes).map_err(|_| "decode failed")?;
+    if decoded != input {
+        return Err("decoded data differs");
+    }
+    // 码表重建出来的树解码结果一样
+    let entries: Vec<_> = (0..=255u8).filter_map(|b| codes.code(b).map(|(code, len)| (b, code, len))).collect();
+    let rebuilt = Codes::from_codes(entries).map_err(|_| "rebuild failed")?;
+    if huffman::decode(&bits, bit_len, &rebuilt).map_err(|_| "rebuilt decode failed")? != input {
+        return Err("rebuilt decode differs");
+    }
+    Ok(bit_len)
+}
+
+// 和 byoct codes --bytes --csv 对同一个输入给出的码一样
+fn abracadabra() -> Result<(), &'static str> {
+    let codes = codes_for(b"abracadabra").map_err(|_| "build failed")?;
+    let expected: [(u8, u128, u8); 5] = [(b'a', 0b0, 1), (b'r', 0b10, 2), (b'b', 0b111, 3), (b'c', 0b1100, 4), (b'd', 0b1101, 4)];
+    for (byte, code, len) in expected {
+        if codes.code(byte) != Some((code, len)) {
+            return Err("codes differ from byoct codes");
+        }
+    }
+    if roundtrip(b"abracadabra")? != 23 {
+        return Err("wrong bit count");
+    }
+    Ok(())
+}
+
+fn single_symbol() -> Result<(), &'static str> {
+    let codes = codes_for(b"zzzz").map_err(|_| "build failed")?;
+    if codes.code(b'z') != Some((0, 1)) {
+        return Err("single symbol must get code 0");
+    }
+    if roundtrip(b"zzzz")? != 4 {
+        return Err("wrong bit count");
+    }
+    Ok(())
+}
+
+fn all_bytes() -> Result<(), &'static str> {
+    let mut input = Vec::new();
+    for i in 0..4096u32 {
+        // 偏斜的分布，码长各不相同
+        input.push((i * i % 257) as u8);
+        input.push((i % 256) as u8);
+    }
+    roundtrip(&input)?;
+    Ok(())
+}
+
+// 斐波那契频率让树尽可能深
+fn deep_tree() -> Result<(), &'static str> {
+    let mut counts = [0usize; huffman::SYMBOLS];
+    let (mut a, mut b) = (1usize, 1usize);
+    for count in counts.iter_mut().take(40) {
+        *count = a;
+        (a, b) = (b, a + b);
+    }
+    let codes = Codes::from_counts(&counts).map_err(|_| "build failed")?;
+    if codes.code(0).map(|(_, len)| len) != Some(39) {
+        return Err("wrong depth");
+    }
+    let input: Vec<u8> = (0..40).collect();
+    let (bits, bit_len) = huffman::encode(&input, &codes).map_err(|_| "encode failed")?;
+    if huffman::decode(&bits, bit_len, &codes).map_err(|_| "decode failed")? != input {
+        return Err("decoded data differs");
+    }
+    Ok(())
+}
+
+fn errors() -> Result<(), &'static str> {
+    if codes_for(b"").err() != Some(CoreError::Empty) {
+        return Err("empty input must fail");
+    }
+    let codes = codes_for(b"abracadabra").map_err(|_| "build failed")?;
+    // 不在树里的字节
+    if huffman::encode(b"xyz", &codes).err() != Some(CoreError::InvalidCode) {
+        return Err("unknown byte must fail");
+    }
+    // 输出切片太小
+    let mut small = [0u8; 2];
+    if huffman::encode_into(b"abracadabra", &codes, &mut small) != Err(CoreError::OutputFull) {
+        return Err("small output must fail");
+    }
+    let (bits, bit_len) = huffman::encode(b"abracadabra", &codes).map_err(|_| "encode failed")?;
+    let mut short = [0u8; 5];
+    if huffman::decode_into(&bits, bit_len, &codes, &mut short) != Err(CoreError::OutputFull) {
+        return Err("small decode output must fail");
+    }
+    // 在一个码的中间结束：最后的 'r' 是 10
+    let (cut, cut_len) = huffman::encode(b"abracadabr", &codes).map_err(|_| "encode failed")?;
+    if huffman::decode(&cut, cut_len - 1, &codes).err() != Some(CoreError::Truncated) {
+        return Err("cut code must fail");
+    }
+    // 比特数比数据长
+    if huffman::decode(&bits, bits.len() * 8 + 1, &codes).err() != Some(CoreError::Truncated) {
+        return Err("short data must fail");
+    }
+    // 不完整的前缀码、重复的前缀
+    if Codes::from_codes([(b'a', 0b0, 1), (b'b', 0b10, 2)]).err() != Some(CoreError::InvalidCodes) {
+        return Err("incomplete codes must fail");
+    }
+    if Codes::from_codes([(b'a', 0b0, 1), (b'b', 0b00, 2), (b'c', 0b1, 1)]).err() != Some(CoreError::InvalidCodes) {
+        return Err("prefix codes must fail");
+    }
+    Ok(())
+}
+
+#[no_mangle]
+extern "C" fn main() -> i32 {
+    let tests: [(&str, fn() -> Result<(), &'static str>); 5] = [
+        ("abracadabra", abracadabra),
+        ("single symbol", single_symbol),
+        ("all bytes", all_bytes),
+        ("deep tree", deep_tree),
+        ("errors", errors),
+    ];
+    let mut failures = 0;
+    for (name, test) in tests {
+        match test() {
+            Ok(()) => println!("ok    {}", name),
+            Err(message) => {
+                println!("FAIL  {}: {}", name, message);
+                failures += 1;
+            },
+        }
+    }
+    if failures > 0 {
+        println!("{} of {} tests failed", failures, tests.len());
+        return 1;
+    }
+    println!("All {} tests passed", tests.len());
+    0
+}
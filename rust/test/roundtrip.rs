@@ -0,0 +1,405 @@
+// 随机生成输入，检查 decompress(compress(x)) == x。
+// 输入包括随机字节、随机 Unicode 文本、极度偏斜的分布和各种退化情况，
+// 每个输入都用几种压缩选项各跑一遍。失败的输入会被缩小成最小的复现用例。
+// 另外检查文件头里的元数据：权限和修改时间的恢复、-N 用记录的文件名、-n 不记录文件名和修改时间；
+// 可随机访问的格式再检查 decompress --range：跨块边界、超出文件末尾和长度为 0 的范围，
+// 以及索引没有覆盖整个文件时报告数据损坏。
+// 用空样本训练的表只有转义一个符号，也要能用 --table 压缩和解压。
+//
+// 在 rust/test 目录下运行：
+//   rustc --edition 2021 ../byoct1.rs -o ../byoct1
+//   rustc --edition 2021 roundtrip.rs && ./roundtrip [../byoct1] [--cases N] [--seed S]
+
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const OPTIONS: &[&[&str]] = &[
+    &[],
+    &["--rle"],
+    &["--order1"],
+    &["--words"],
+    &["--preset", "english"],
+    &["--rle", "--words", "--order1"],
+    &["--seekable", "--block-size", "7"],
+    &["--seekable", "--block-size", "64", "--block-tables"],
+];
+
+// 检查 --range 时用的块大小，和 OPTIONS 里的一样
+const RANGE_OPTIONS: &[&[&str]] = &[
+    &["--seekable", "--block-size", "7"],
+    &["--seekable", "--block-size", "64", "--block-tables"],
+];
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+fn random_bytes(rng: &mut Rng) -> Vec<u8> {
+    let len = rng.below(2000);
+    (0..len).map(|_| rng.next() as u8).collect()
+}
+
+fn random_unicode(rng: &mut Rng) -> Vec<u8> {
+    // 各种宽度的 UTF-8 字符，包括非字符 U+FFFF 和辅助平面
+    const RANGES: &[(u32, u32)] = &[(0x00, 0x7f), (0x80, 0x7ff), (0x800, 0xd7ff), (0xe000, 0xffff), (0x10000, 0x10ffff)];
+    let len = rng.below(800);
+    let mut text = String::new();
+    for _ in 0..len {
+        let (low, high) = RANGES[rng.below(RANGES.len())];
+        text.push(char::from_u32(low + rng.below((high - low + 1) as usize) as u32).unwrap());
+    }
+    text.into_bytes()
+}
+
+fn skewed(rng: &mut Rng) -> Vec<u8> {
+    // 几乎全是同一个字节，偶尔夹杂别的字节和很长的游程
+    let len = rng.below(20000);
+    let common = rng.next() as u8;
+    let mut out = Vec::with_capacity(len);
+    while out.len() < len {
+        match rng.below(100) {
+            0 => out.push(rng.next() as u8),
+            1 => out.extend(std::iter::repeat(rng.next() as u8).take(rng.below(100000))),
+            _ => out.push(common),
+        }
+    }
+    out
+}
+
+fn degenerate() -> Vec<Vec<u8>> {
+    vec![
+        Vec::new(),
+        vec![b'a'],
+        vec![0],
+        vec![0xff],
+        vec![b'x'; 100000],
+        b"abababababababab".to_vec(),
+        (0..=255).collect(),
+        "\u{ffff}\u{ffff}\u{ffff}\u{ffff}\u{ffff}".as_bytes().to_vec(),
+        "        \n\n\n\n\t\t\t\t".as_bytes().to_vec(),
+        "\"\\{}:,".as_bytes().to_vec(),
+    ]
+}
+
+// 返回 None 表示通过，否则返回失败原因
+// 压缩把 case.bin 换成 case.bin.byoct，解压再换回来
+fn roundtrip(byoct: &str, dir: &Path, input: &[u8], options: &[&str]) -> Option<String> {
+    let _ = fs::remove_file(dir.join("case.bin.byoct"));
+    fs::write(dir.join("case.bin"), input).unwrap();
+
+    let status = Command::new(byoct).args(options).arg("case.bin").current_dir(dir).output();
+    match status {
+        Ok(output) if output.status.success() => (),
+        Ok(output) => return Some(format!("compress failed: {}", first_line(&output.stderr))),
+        Err(e) => return Some(format!("cannot run {}: {}", byoct, e)),
+    }
+    if dir.join("case.bin").exists() {
+        return Some(String::from("input file was not removed after compressing"));
+    }
+    let output = Command::new(byoct).arg("case.bin.byoct").current_dir(dir).output().unwrap();
+    if !output.status.success() {
+        return Some(format!("decompress failed: {}", first_line(&output.stderr)));
+    }
+    match fs::read(dir.join("case.bin")) {
+        Ok(decoded) if decoded == input => None,
+        Ok(decoded) => Some(format!("decoded {} bytes, expected {}", decoded.len(), input.len())),
+        Err(e) => Some(format!("no decoded output: {}", e)),
+    }
+}
+
+// 用 RANGE_OPTIONS 压缩成可随机访问的格式，每个范围的 --range 输出要和输入的那一段一样。
+// 返回 None 表示通过，否则返回失败原因
+fn check_ranges(byoct: &str, dir: &Path, input: &[u8]) -> Option<String> {
+    let len = input.len() as u64;
+    for options in RANGE_OPTIONS {
+        let block = options[2].parse::<u64>().unwrap();
+        // (起点, 长度)：块内、正好一块、跨块边界、从边界开始长度为 0、到末尾、超出末尾、起点在末尾之后
+        let ranges = [(0, 0), (1, 3), (block, block), (block - 1, 2), (block * 2, 0), (0, len), (len / 2, len), (len, 5), (len + 10, 10)];
+        fs::write(dir.join("range.bin"), input).unwrap();
+        if let Err(reason) = run(byoct, dir, &[*options, &["-f", "-k", "range.bin"]].concat()) {
+            return Some(reason);
+        }
+        for (start, count) in ranges {
+            let range = format!("{}:{}", start, count);
+            let output = Command::new(byoct).args(["decompress", "--range", &range, "range.bin.byoct"]).current_dir(dir).output().unwrap();
+            if !output.status.success() {
+                return Some(format!("{:?} --range {} failed: {}", options, range, first_line(&output.stderr)));
+            }
+            let from = start.min(len) as usize;
+            let expected = &input[from..(start + count).min(len) as usize];
+            if output.stdout != expected {
+                return Some(format!("{:?} --range {} gave {} bytes, expected {}", options, range, output.stdout.len(), expected.len()));
+            }
+        }
+        // 从索引里去掉第一块（尾部的索引偏移后移一项、块数减一），剩下的块都完好，
+        // 但开头没有块覆盖，要报告数据损坏而不是少输出一块
+        if len > block {
+            let mut bytes = fs::read(dir.join("range.bin.byoct")).unwrap();
+            let trailer = bytes.len() - 24;
+            let index_offset = u64::from_le_bytes(bytes[trailer..trailer + 8].try_into().unwrap());
+            let count = u32::from_le_bytes(bytes[trailer + 8..trailer + 12].try_into().unwrap());
+            bytes[trailer..trailer + 8].copy_from_slice(&(index_offset + 20).to_le_bytes());
+            bytes[trailer + 8..trailer + 12].copy_from_slice(&(count - 1).to_le_bytes());
+            fs::write(dir.join("range.bin.byoct"), bytes).unwrap();
+            let range = format!("0:{}", len);
+            let output = Command::new(byoct).args(["decompress", "--range", &range, "range.bin.byoct"]).current_dir(dir).output().unwrap();
+            if output.status.code() != Some(4) {
+                return Some(format!("{:?} index without the first block exited with {:?}, expected 4", options, output.status.code()));
+            }
+        }
+    }
+    let _ = fs::remove_file(dir.join("range.bin"));
+    let _ = fs::remove_file(dir.join("range.bin.byoct"));
+    None
+}
+
+// 用空样本训练一张表，每个输入用 --table 压缩再解压。返回失败原因的列表
+fn check_empty_table(byoct: &str, dir: &Path, inputs: &[&[u8]]) -> Vec<String> {
+    let mut failures = Vec::new();
+    fs::write(dir.join("empty.sample"), "").unwrap();
+    if let Err(reason) = run(byoct, dir, &["train", "-o", "empty.bct", "empty.sample"]) {
+        failures.push(reason);
+    }
+    for input in inputs {
+        fs::write(dir.join("table.txt"), input).unwrap();
+        let result = run(byoct, dir, &["--table", "empty.bct", "-f", "table.txt"])
+            .and_then(|_| run(byoct, dir, &["--table", "empty.bct", "-f", "table.txt.byoct"]));
+        match (result, fs::read(dir.join("table.txt"))) {
+            (Err(reason), _) => failures.push(reason),
+            (Ok(()), Ok(decoded)) if decoded == *input => {},
+            (Ok(()), _) => failures.push(format!("{:?} did not round-trip with a table trained on an empty sample", String::from_utf8_lossy(input))),
+        }
+    }
+    for name in ["empty.sample", "empty.bct", "table.txt", "table.txt.byoct"] {
+        let _ = fs::remove_file(dir.join(name));
+    }
+    failures
+}
+
+// 压缩或解压 args，返回 Err(失败原因)
+fn run(byoct: &str, dir: &Path, args: &[&str]) -> Result<(), String> {
+    match Command::new(byoct).args(args).current_dir(dir).output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!("{:?} failed: {}", args, first_line(&output.stderr))),
+        Err(e) => Err(format!("cannot run {}: {}", byoct, e)),
+    }
+}
+
+fn mode_and_mtime(path: &Path) -> Result<(u32, SystemTime), String> {
+    let info = fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok((info.permissions().mode() & 0o7777, info.modified().unwrap()))
+}
+
+// 建一个权限是 0640、修改时间是 MTIME 的 meta.txt
+const MTIME: u64 = 1_000_000_000;
+
+fn make_meta_file(dir: &Path) -> PathBuf {
+    let path = dir.join("meta.txt");
+    let _ = fs::remove_file(&path);
+    fs::write(&path, "metadata test\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    fs::File::options().write(true).open(&path).unwrap().set_modified(UNIX_EPOCH + Duration::from_secs(MTIME)).unwrap();
+    path
+}
+
+// 返回失败原因的列表
+fn check_metadata(byoct: &str, dir: &Path) -> Vec<String> {
+    let mut failures = Vec::new();
+    let mut check = |result: Result<(), String>| {
+        if let Err(reason) = result {
+            failures.push(reason);
+        }
+    };
+
+    // 压缩文件的权限和输入一样，解压恢复权限和修改时间
+    check((|| {
+        let path = make_meta_file(dir);
+        run(byoct, dir, &["meta.txt"])?;
+        match mode_and_mtime(&dir.join("meta.txt.byoct"))? {
+            (0o640, _) => {},
+            (mode, _) => return Err(format!("compressed file has mode {:o}, expected 640", mode)),
+        }
+        run(byoct, dir, &["meta.txt.byoct"])?;
+        match mode_and_mtime(&path)? {
+            (0o640, mtime) if mtime == UNIX_EPOCH + Duration::from_secs(MTIME) => Ok(()),
+            (mode, mtime) => Err(format!("restored mode {:o} and mtime {:?}, expected 640 and {}", mode, mtime, MTIME)),
+        }
+    })());
+
+    // -N 用记录的文件名，不管压缩文件改成了什么名字
+    check((|| {
+        make_meta_file(dir);
+        run(byoct, dir, &["meta.txt"])?;
+        fs::rename(dir.join("meta.txt.byoct"), dir.join("renamed.byoct")).unwrap();
+        run(byoct, dir, &["-N", "renamed.byoct"])?;
+        if !dir.join("meta.txt").exists() || dir.join("renamed").exists() {
+            return Err(String::from("-N did not use the recorded name meta.txt"));
+        }
+        Ok(())
+    })());
+
+    // -n 不记录文件名和修改时间，-N 只能退回去掉后缀的名字，权限照样恢复
+    check((|| {
+        make_meta_file(dir);
+        run(byoct, dir, &["-n", "meta.txt"])?;
+        fs::rename(dir.join("meta.txt.byoct"), dir.join("renamed.byoct")).unwrap();
+        run(byoct, dir, &["-N", "renamed.byoct"])?;
+        if dir.join("meta.txt").exists() {
+            return Err(String::from("-n still recorded the name"));
+        }
+        match mode_and_mtime(&dir.join("renamed"))? {
+            (_, mtime) if mtime == UNIX_EPOCH + Duration::from_secs(MTIME) => Err(String::from("-n still recorded the mtime")),
+            (0o640, _) => Ok(()),
+            (mode, _) => Err(format!("-n restored mode {:o}, expected 640", mode)),
+        }
+    })());
+
+    // setuid 之类的特殊位压缩时不记录；伪造的压缩文件里带了也不恢复
+    check((|| {
+        let path = make_meta_file(dir);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4755)).unwrap();
+        run(byoct, dir, &["meta.txt"])?;
+        match mode_and_mtime(&dir.join("meta.txt.byoct"))? {
+            (0o755, _) => {},
+            (mode, _) => return Err(format!("compressed file of a 4755 input has mode {:o}, expected 755", mode)),
+        }
+        run(byoct, dir, &["meta.txt.byoct"])?;
+        match mode_and_mtime(&path)? {
+            (0o755, _) => {},
+            (mode, _) => return Err(format!("4755 input restored as {:o}, expected 755", mode)),
+        }
+        // 权限在 MAGIC | VERSION | flags | 字段标志 | 名字长度 | "meta.txt" 之后
+        run(byoct, dir, &["meta.txt"])?;
+        let mut bytes = fs::read(dir.join("meta.txt.byoct")).unwrap();
+        bytes[17..21].copy_from_slice(&0o6755u32.to_le_bytes());
+        fs::write(dir.join("meta.txt.byoct"), bytes).unwrap();
+        run(byoct, dir, &["meta.txt.byoct"])?;
+        match mode_and_mtime(&path)? {
+            (0o755, _) => Ok(()),
+            (mode, _) => Err(format!("recorded mode 6755 restored as {:o}, expected 755", mode)),
+        }
+    })());
+
+    for name in ["meta.txt", "meta.txt.byoct", "renamed", "renamed.byoct"] {
+        let _ = fs::remove_file(dir.join(name));
+    }
+    failures
+}
+
+fn first_line(stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("").to_string()
+}
+
+// 缩小失败的输入：先尝试删掉大块，再删单个字节，最后把字节换成更简单的值，
+// 只要还失败就保留修改
+fn shrink(byoct: &str, dir: &Path, input: &[u8], options: &[&str]) -> Vec<u8> {
+    let mut current = input.to_vec();
+    let mut chunk = current.len() / 2;
+    while chunk > 0 {
+        let mut start = 0;
+        while start < current.len() {
+            let end = (start + chunk).min(current.len());
+            let mut candidate = current[..start].to_vec();
+            candidate.extend_from_slice(&current[end..]);
+            if roundtrip(byoct, dir, &candidate, options).is_some() {
+                current = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    for i in 0..current.len() {
+        for simpler in [b'a', b'0', 0] {
+            if current[i] == simpler {
+                break;
+            }
+            let mut candidate = current.clone();
+            candidate[i] = simpler;
+            if roundtrip(byoct, dir, &candidate, options).is_some() {
+                current = candidate;
+                break;
+            }
+        }
+    }
+    current
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut byoct = String::from("../byoct1");
+    let mut cases = 50;
+    let mut seed: u64 = 0x853C_49E6_748F_EA9B;
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--cases" => cases = iter.next().and_then(|n| n.parse().ok()).unwrap_or(cases),
+            "--seed" => seed = iter.next().and_then(|n| n.parse().ok()).unwrap_or(seed),
+            _ => byoct = arg.clone(),
+        }
+    }
+    // 子进程的工作目录不同，相对路径要先转成绝对路径
+    if byoct.contains('/') {
+        byoct = fs::canonicalize(&byoct).map(|path| path.display().to_string()).unwrap_or(byoct);
+    }
+    println!("seed {}", seed);
+
+    let dir: PathBuf = env::temp_dir().join(format!("byoct_roundtrip_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut rng = Rng(seed);
+    let mut inputs: Vec<(String, Vec<u8>)> = degenerate().into_iter().map(|input| (String::from("degenerate"), input)).collect();
+    for _ in 0..cases {
+        inputs.push((String::from("bytes"), random_bytes(&mut rng)));
+        inputs.push((String::from("unicode"), random_unicode(&mut rng)));
+        inputs.push((String::from("skewed"), skewed(&mut rng)));
+    }
+
+    let mut failures = 0;
+    for (kind, input) in inputs.iter() {
+        for options in OPTIONS {
+            if let Some(reason) = roundtrip(&byoct, &dir, input, options) {
+                let minimal = shrink(&byoct, &dir, input, options);
+                println!("FAIL {} input ({} bytes) with {:?}: {}", kind, input.len(), options, reason);
+                println!("     minimal reproducer ({} bytes): {:?}", minimal.len(), String::from_utf8_lossy(&minimal));
+                println!("     bytes: {:02x?}", minimal);
+                failures += 1;
+            }
+        }
+        if let Some(reason) = check_ranges(&byoct, &dir, input) {
+            println!("FAIL {} input ({} bytes) ranges: {}", kind, input.len(), reason);
+            failures += 1;
+        }
+    }
+    for reason in check_metadata(&byoct, &dir) {
+        println!("FAIL metadata: {}", reason);
+        failures += 1;
+    }
+    for reason in check_empty_table(&byoct, &dir, &[b"", b"a", "h\u{e9}llo, w\u{f6}rld\n".as_bytes()]) {
+        println!("FAIL empty table: {}", reason);
+        failures += 1;
+    }
+    let _ = fs::remove_dir_all(&dir);
+
+    if failures > 0 {
+        println!("{} failures", failures);
+        process::exit(1);
+    }
+    println!("{} inputs round-tripped with {} option sets, ranges, metadata and an empty table checked", inputs.len(), OPTIONS.len());
+}
@@ -0,0 +1,196 @@
+// byoct tree --trace [--format text|json|html] <file>
+// 记录建树的每一步：从队列里取出的两个节点、它们的权重、新的父节点和合并之后的队列，
+// 最后是每个符号分到的编码。html 输出是一个可以一步一步回放的页面。
+// 叶子用符号表示，第 k 次合并产生的内部节点叫 Nk。
+
+use std::collections::HashMap;
+
+use super::stats::{escape_json, escape_symbol};
+use super::{build_huffman_codes, build_huffman_tree_with, HuffmanNode};
+
+struct Merge {
+    left: (String, usize),
+    right: (String, usize),
+    parent: (String, usize),
+    queue: Vec<(String, usize)>,
+}
+
+pub struct Trace {
+    initial: Vec<(String, usize)>,
+    merges: Vec<Merge>,
+    codes: Vec<(String, String)>,
+}
+
+// 同一时刻队列里的子树互不相交，而父节点的权重总比它的左孩子大，
+// 所以 (最左边的叶子, 权重) 可以唯一确定一个内部节点
+fn leftmost(node: &HuffmanNode) -> char {
+    match (node.ch, &node.left) {
+        (Some(ch), _) => ch,
+        (None, Some(left)) => leftmost(left),
+        (None, None) => '\0',
+    }
+}
+
+fn label(node: &HuffmanNode, names: &HashMap<(char, usize), String>, bytes_mode: bool) -> (String, usize) {
+    let name = match node.ch {
+        Some(ch) => escape_symbol(ch, bytes_mode),
+        None => names.get(&(leftmost(node), node.freq)).cloned().unwrap_or_default(),
+    };
+    (name, node.freq)
+}
+
+pub fn record(hashmap: &HashMap<char, usize>, bytes_mode: bool) -> Trace {
+    let mut initial: Vec<(char, usize)> = hashmap.iter().map(|(ch, freq)| (*ch, *freq)).collect();
+    initial.sort_by_key(|(ch, freq)| (*freq, *ch));
+    let initial = initial.into_iter().map(|(ch, freq)| (escape_symbol(ch, bytes_mode), freq)).collect();
+
+    let mut names = HashMap::new();
+    let mut merges = Vec::new();
+    let root = build_huffman_tree_with(hashmap, |queue, index| {
+        let parent = &queue[index];
+        names.insert((leftmost(parent), parent.freq), format!("N{}", merges.len() + 1));
+        merges.push(Merge {
+            left: label(parent.left.as_ref().unwrap(), &names, bytes_mode),
+            right: label(parent.right.as_ref().unwrap(), &names, bytes_mode),
+            parent: label(parent, &names, bytes_mode),
+            queue: queue.iter().map(|node| label(node, &names, bytes_mode)).collect(),
+        });
+    });
+
+    let mut codes: Vec<(char, String)> = build_huffman_codes(&root).into_iter().collect();
+    codes.sort_by(|a, b| a.1.len().cmp(&b.1.len()).then(a.1.cmp(&b.1)));
+    let codes = codes.into_iter().map(|(ch, code)| (escape_symbol(ch, bytes_mode), code)).collect();
+
+    Trace { initial, merges, codes }
+}
+
+fn queue_text(queue: &[(String, usize)]) -> String {
+    queue.iter().map(|(name, freq)| format!("{}:{}", name, freq)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn to_text(trace: &Trace) -> String {
+    let mut out = format!("Initial queue: {}\n", queue_text(&trace.initial));
+    for (i, merge) in trace.merges.iter().enumerate() {
+        out.push_str(&format!(
+            "Step {}: merge {} ({}) + {} ({}) -> {} ({})\n",
+            i + 1, merge.left.0, merge.left.1, merge.right.0, merge.right.1, merge.parent.0, merge.parent.1
+        ));
+        out.push_str(&format!("    queue: {}\n", queue_text(&merge.queue)));
+    }
+    out.push_str("Codes:\n");
+    for (symbol, code) in trace.codes.iter() {
+        out.push_str(&format!("    {:>8}  {}\n", symbol, code));
+    }
+    out
+}
+
+fn queue_json(queue: &[(String, usize)]) -> String {
+    let items: Vec<String> = queue.iter()
+        .map(|(name, freq)| format!("{{\"node\": \"{}\", \"weight\": {}}}", escape_json(name), freq))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+pub fn to_json(trace: &Trace) -> String {
+    let mut out = String::from("{\n");
+    out.push_str(&format!("  \"initial\": {},\n", queue_json(&trace.initial)));
+    out.push_str("  \"merges\": [\n");
+    for (i, merge) in trace.merges.iter().enumerate() {
+        out.push_str(&format!(
+            "    {{\"step\": {}, \"left\": {{\"node\": \"{}\", \"weight\": {}}}, \"right\": {{\"node\": \"{}\", \"weight\": {}}}, \"parent\": {{\"node\": \"{}\", \"weight\": {}}}, \"queue\": {}}}{}\n",
+            i + 1, escape_json(&merge.left.0), merge.left.1, escape_json(&merge.right.0), merge.right.1,
+            escape_json(&merge.parent.0), merge.parent.1, queue_json(&merge.queue),
+            if i + 1 < trace.merges.len() { "," } else { "" }
+        ));
+    }
+    out.push_str("  ],\n  \"codes\": [\n");
+    for (i, (symbol, code)) in trace.codes.iter().enumerate() {
+        out.push_str(&format!(
+            "    {{\"symbol\": \"{}\", \"code\": \"{}\"}}{}\n",
+            escape_json(symbol), code, if i + 1 < trace.codes.len() { "," } else { "" }
+        ));
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
+
+pub fn to_html(trace: &Trace) -> String {
+    // 数据直接嵌进页面，"</" 要转义，否则会提前结束 <script>
+    let data = to_json(trace).replace("</", "<\\/");
+    HTML_TEMPLATE.replace("/*TRACE*/", &data)
+}
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Huffman tree construction</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+.queue span { display: inline-block; margin: 2px; padding: 4px 8px; border: 1px solid #999; font-family: monospace; }
+.queue span.parent { background: #dde8ff; font-weight: bold; }
+td { font-family: monospace; padding: 0 1em; }
+</style>
+</head>
+<body>
+<h1>Huffman tree construction</h1>
+<p>
+<button id="prev">&larr; Prev</button>
+<button id="next">Next &rarr;</button>
+<button id="play">Play</button>
+<span id="step"></span>
+</p>
+<p id="merge"></p>
+<div class="queue" id="queue"></div>
+<table id="codes" hidden><tr><th>Symbol</th><th>Code</th></tr></table>
+<script>
+const trace = /*TRACE*/;
+let step = 0;
+let timer = null;
+function show() {
+    const last = trace.merges.length;
+    document.getElementById("step").textContent = "Step " + step + " / " + (last + 1);
+    const queue = document.getElementById("queue");
+    queue.innerHTML = "";
+    let nodes = trace.initial;
+    let merge = null;
+    if (step > 0 && step <= last) {
+        merge = trace.merges[step - 1];
+        nodes = merge.queue;
+        document.getElementById("merge").textContent = "Merge " + merge.left.node + " (" + merge.left.weight + ") + "
+            + merge.right.node + " (" + merge.right.weight + ") -> " + merge.parent.node + " (" + merge.parent.weight + ")";
+    } else if (step > last) {
+        nodes = last > 0 ? trace.merges[last - 1].queue : trace.initial;
+        document.getElementById("merge").textContent = "Final codes";
+    } else {
+        document.getElementById("merge").textContent = "Initial queue, sorted by weight";
+    }
+    for (const node of nodes) {
+        const span = document.createElement("span");
+        span.textContent = node.node + ":" + node.weight;
+        if (merge && node.node === merge.parent.node) span.className = "parent";
+        queue.appendChild(span);
+    }
+    document.getElementById("codes").hidden = step <= last;
+}
+const table = document.getElementById("codes");
+for (const entry of trace.codes) {
+    const row = table.insertRow();
+    row.insertCell().textContent = entry.symbol;
+    row.insertCell().textContent = entry.code;
+}
+document.getElementById("prev").onclick = () => { if (step > 0) step--; show(); };
+document.getElementById("next").onclick = () => { if (step <= trace.merges.length) step++; show(); };
+document.getElementById("play").onclick = () => {
+    if (timer) { clearInterval(timer); timer = null; return; }
+    timer = setInterval(() => {
+        if (step > trace.merges.length) { clearInterval(timer); timer = null; return; }
+        step++;
+        show();
+    }, 800);
+};
+show();
+</script>
+</body>
+</html>
+"#;
@@ -0,0 +1,163 @@
+// byoct tree [--format dot|svg] [--bytes] <file>
+// 把文件的 Huffman 树画出来：内部节点显示权重，叶子显示符号和权重，边上标 0/1。
+// dot 可以交给 Graphviz 处理，svg 是自己排版的，可以直接打开。
+// 加上 --trace 时输出建树的过程，见 trace.rs。
+
+use std::fs;
+
+use super::stats::escape_symbol;
+use super::trace;
+use super::{build_hashmap, build_huffman_tree, error, Error, HuffmanNode};
+
+// SVG 里每个叶子占的宽度和每层的高度
+const X_SPACING: f64 = 56.0;
+const Y_SPACING: f64 = 72.0;
+const MARGIN: f64 = 40.0;
+
+pub fn run(args: &[String]) {
+    let mut bytes_mode = false;
+    let mut trace = false;
+    let mut format = None;
+    let mut input_file = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--bytes" => bytes_mode = true,
+            "--trace" => trace = true,
+            "--format" => format = iter.next().cloned(),
+            _ => input_file = Some(arg),
+        }
+    }
+    let format = format.unwrap_or_else(|| String::from(if trace { "text" } else { "dot" }));
+    let valid_format = match trace {
+        true => ["text", "json", "html"].contains(&format.as_str()),
+        false => ["dot", "svg"].contains(&format.as_str()),
+    };
+    let input_file = match input_file {
+        Some(input_file) if valid_format => input_file,
+        _ => {
+            println!("Usage: byoct tree [--format dot|svg] [--bytes] <input_file>");
+            println!("       byoct tree --trace [--format text|json|html] [--bytes] <input_file>");
+            std::process::exit(1);
+        }
+    };
+
+    let contents = fs::read(input_file)
+        .unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", input_file), e)));
+    let text = match std::str::from_utf8(&contents) {
+        Ok(text) if !bytes_mode => text.to_string(),
+        _ => {
+            bytes_mode = true;
+            contents.iter().map(|&byte| char::from(byte)).collect()
+        },
+    };
+
+    let hashmap = build_hashmap(&text);
+    if hashmap.is_empty() {
+        println!("Empty input, no tree to draw");
+        return;
+    }
+    if trace {
+        let trace = trace::record(&hashmap, bytes_mode);
+        let output = match format.as_str() {
+            "json" => trace::to_json(&trace),
+            "html" => trace::to_html(&trace),
+            _ => trace::to_text(&trace),
+        };
+        print!("{}", output);
+        return;
+    }
+    let root = build_huffman_tree(&hashmap);
+    let output = if format == "svg" { to_svg(&root, bytes_mode) } else { to_dot(&root, bytes_mode) };
+    print!("{}", output);
+}
+
+pub fn to_dot(root: &HuffmanNode, bytes_mode: bool) -> String {
+    let mut out = String::from("digraph huffman {\n    node [fontname=\"monospace\"];\n");
+    let mut next_id = 0;
+    dot_node(root, bytes_mode, &mut next_id, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+// 返回节点编号
+fn dot_node(node: &HuffmanNode, bytes_mode: bool, next_id: &mut usize, out: &mut String) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    match node.ch {
+        Some(ch) => {
+            let symbol = escape_symbol(ch, bytes_mode).replace('\\', "\\\\").replace('"', "\\\"");
+            out.push_str(&format!("    n{} [shape=box, label=\"{}\\n{}\"];\n", id, symbol, node.freq));
+        },
+        None => out.push_str(&format!("    n{} [shape=circle, label=\"{}\"];\n", id, node.freq)),
+    }
+    for (bit, child) in [("0", &node.left), ("1", &node.right)] {
+        if let Some(child) = child {
+            let child_id = dot_node(child, bytes_mode, next_id, out);
+            out.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n", id, child_id, bit));
+        }
+    }
+    id
+}
+
+pub fn to_svg(root: &HuffmanNode, bytes_mode: bool) -> String {
+    // 叶子从左到右依次排开，内部节点放在两个孩子中间
+    let mut shapes = String::new();
+    let mut edges = String::new();
+    let mut next_leaf = 0;
+    let mut max_depth = 0;
+    svg_node(root, 0, bytes_mode, &mut next_leaf, &mut max_depth, &mut shapes, &mut edges);
+
+    let width = MARGIN * 2.0 + (next_leaf.max(1) - 1) as f64 * X_SPACING;
+    let height = MARGIN * 2.0 + max_depth as f64 * Y_SPACING;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"12\" text-anchor=\"middle\">\n",
+        width, height
+    );
+    out.push_str(&edges);
+    out.push_str(&shapes);
+    out.push_str("</svg>\n");
+    out
+}
+
+// 返回节点中心坐标
+fn svg_node(node: &HuffmanNode, depth: usize, bytes_mode: bool, next_leaf: &mut usize, max_depth: &mut usize,
+    shapes: &mut String, edges: &mut String) -> (f64, f64) {
+    *max_depth = (*max_depth).max(depth);
+    let y = MARGIN + depth as f64 * Y_SPACING;
+
+    if let Some(ch) = node.ch {
+        let x = MARGIN + *next_leaf as f64 * X_SPACING;
+        *next_leaf += 1;
+        shapes.push_str(&format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"48\" height=\"32\" fill=\"#fff7d6\" stroke=\"#333\"/>\n",
+            x - 24.0, y - 16.0
+        ));
+        shapes.push_str(&format!("  <text x=\"{}\" y=\"{}\">{}</text>\n", x, y - 2.0, escape_xml(&escape_symbol(ch, bytes_mode))));
+        shapes.push_str(&format!("  <text x=\"{}\" y=\"{}\" font-size=\"10\">{}</text>\n", x, y + 11.0, node.freq));
+        return (x, y);
+    }
+
+    let mut children = Vec::new();
+    for (bit, child) in [("0", &node.left), ("1", &node.right)] {
+        if let Some(child) = child {
+            let (cx, cy) = svg_node(child, depth + 1, bytes_mode, next_leaf, max_depth, shapes, edges);
+            children.push((bit, cx, cy));
+        }
+    }
+    let x = children.iter().map(|(_, cx, _)| cx).sum::<f64>() / children.len().max(1) as f64;
+    for (bit, cx, cy) in children {
+        edges.push_str(&format!("  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#666\"/>\n", x, y, cx, cy));
+        edges.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" fill=\"#c00\">{}</text>\n",
+            (x + cx) / 2.0 + if bit == "0" { -8.0 } else { 8.0 }, (y + cy) / 2.0, bit
+        ));
+    }
+    shapes.push_str(&format!("  <circle cx=\"{}\" cy=\"{}\" r=\"16\" fill=\"#dde8ff\" stroke=\"#333\"/>\n", x, y));
+    shapes.push_str(&format!("  <text x=\"{}\" y=\"{}\">{}</text>\n", x, y + 4.0, node.freq));
+    (x, y)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
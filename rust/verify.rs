@@ -0,0 +1,171 @@
+// byoct verify [--bless] <dir>
+// 回归测试用的金样本语料。目录下的 MANIFEST 每行一个用例：
+//   <输入文件> <期望的压缩文件> [压缩选项...]
+// 对每个用例检查两件事：
+//   1. 现在的编码器压缩输入，结果和期望的文件逐字节相同
+//   2. 期望的文件（旧版本写出的）解压之后和输入相同
+// 语料按格式版本分目录（test/corpus/v1 ...），格式改变时加一个新目录，旧目录保留，
+// 保证旧文件一直能解码。<dir> 本身没有 MANIFEST 时，检查它下面每个有 MANIFEST 的子目录。
+// --bless 只生成还不存在的期望文件，用来添加新用例，已有的文件从不覆盖。
+// 选项里有 --seekable [--block-size N] [--block-tables] 时用可随机访问的格式压缩。
+// --name NAME、--mode 八进制权限、--mtime Unix 秒把固定的元数据写进文件头（原始长度自动记录），
+// 还检查期望文件里读出的元数据和它们一样。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::progress::Progress;
+use super::{compress, decompress, error, read_metadata, seekable, Error, Metadata, Options};
+
+const MANIFEST: &str = "MANIFEST";
+
+struct Case {
+    input: String,
+    expected: String,
+    options: Options,
+    // 可随机访问的格式的 (块大小, 每块一张表)
+    seekable: Option<(usize, bool)>,
+}
+
+pub fn run(args: &[String]) {
+    let mut bless = false;
+    let mut dir = None;
+    for arg in args {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            _ => dir = Some(arg),
+        }
+    }
+    let dir = match dir {
+        Some(dir) => Path::new(dir),
+        None => {
+            println!("Usage: byoct verify [--bless] <corpus_dir>");
+            std::process::exit(1);
+        }
+    };
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if dir.join(MANIFEST).exists() {
+        dirs.push(dir.to_path_buf());
+    } else {
+        let entries = fs::read_dir(dir).unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", dir.display()), e)));
+        for entry in entries.flatten() {
+            if entry.path().join(MANIFEST).exists() {
+                dirs.push(entry.path());
+            }
+        }
+        dirs.sort();
+    }
+    if dirs.is_empty() {
+        println!("No {} found in {}", MANIFEST, dir.display());
+        std::process::exit(1);
+    }
+
+    let mut checked = 0;
+    let mut failures = 0;
+    for dir in dirs {
+        let path = dir.join(MANIFEST);
+        let manifest = fs::read_to_string(&path).unwrap_or_else(|e| error::exit(Error::Io(format!("Error reading {}", path.display()), e)));
+        for (line_number, line) in manifest.lines().enumerate() {
+            let case = match parse_line(&dir, line) {
+                Ok(Some(case)) => case,
+                Ok(None) => continue,
+                Err(e) => {
+                    println!("FAIL {}:{}: {}", dir.join(MANIFEST).display(), line_number + 1, e);
+                    failures += 1;
+                    continue;
+                },
+            };
+            let name = format!("{}/{}", dir.display(), case.expected);
+            checked += 1;
+            match verify_case(&dir, &case, bless) {
+                Ok(true) => println!("NEW  {}", name),
+                Ok(false) => println!("PASS {}", name),
+                Err(e) => {
+                    println!("FAIL {}: {}", name, e);
+                    failures += 1;
+                },
+            }
+        }
+    }
+
+    if failures > 0 {
+        println!("{} of {} cases failed", failures, checked);
+        std::process::exit(1);
+    }
+    println!("{} cases verified", checked);
+}
+
+// 空行和 # 开头的注释返回 None；--table 的路径相对于语料目录
+fn parse_line(dir: &Path, line: &str) -> Result<Option<Case>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let mut fields = line.split_whitespace();
+    let input = fields.next().unwrap().to_string();
+    let expected = fields.next().ok_or("missing expected file")?.to_string();
+    let mut args: Vec<String> = fields.map(String::from).collect();
+    for i in 1..args.len() {
+        if args[i - 1] == "--table" {
+            args[i] = dir.join(&args[i]).to_string_lossy().into_owned();
+        }
+    }
+    let mut fields = args.iter().map(|arg| arg.as_str());
+    let mut options = Options::default();
+    let (mut seekable, mut block_size, mut block_tables) = (false, seekable::DEFAULT_BLOCK_SIZE, false);
+    while let Some(field) = fields.next() {
+        match field {
+            "--seekable" => seekable = true,
+            "--block-tables" => block_tables = true,
+            "--block-size" => block_size = fields.next().and_then(|n| n.parse().ok()).ok_or("--block-size needs a number of bytes")?,
+            "--name" => options.metadata.get_or_insert_with(Metadata::default).name = Some(fields.next().ok_or("--name needs a file name")?.to_string()),
+            "--mode" => options.metadata.get_or_insert_with(Metadata::default).mode =
+                Some(fields.next().and_then(|mode| u32::from_str_radix(mode, 8).ok()).ok_or("--mode needs an octal mode")?),
+            "--mtime" => options.metadata.get_or_insert_with(Metadata::default).mtime =
+                Some(fields.next().and_then(|mtime| mtime.parse().ok()).ok_or("--mtime needs a number of seconds")?),
+            _ if options.parse_arg(field, &mut fields).map_err(|e| e.to_string())? => {},
+            _ => return Err(format!("unknown option '{}'", field)),
+        }
+    }
+    options.check().map_err(|e| e.to_string())?;
+    if seekable && (options.rle || options.order1 || options.words || options.preset.is_some() || options.table.is_some() || options.metadata.is_some()) {
+        return Err(String::from("--seekable cannot be combined with other compression options"));
+    }
+    Ok(Some(Case { input, expected, options, seekable: seekable.then_some((block_size, block_tables)) }))
+}
+
+// 返回 true 表示期望文件是这次新生成的
+fn verify_case(dir: &Path, case: &Case, bless: bool) -> Result<bool, String> {
+    let input = fs::read(dir.join(&case.input)).map_err(|e| format!("cannot read {}: {}", case.input, e))?;
+    let compressed = match case.seekable {
+        Some((block_size, block_tables)) => seekable::compress(&input, block_size, block_tables, &mut Progress::default())
+            .map_err(|e| format!("cannot compress: {}", e))?,
+        None => compress(&input, &case.options),
+    };
+
+    let expected_path = dir.join(&case.expected);
+    if bless && !expected_path.exists() {
+        fs::write(&expected_path, &compressed).map_err(|e| format!("cannot write {}: {}", case.expected, e))?;
+        return Ok(true);
+    }
+    let expected = fs::read(&expected_path).map_err(|e| format!("cannot read {}: {}", case.expected, e))?;
+
+    let decoded = decompress(&expected, case.options.table.as_slice()).map_err(|e| format!("expected file does not decode: {}", e))?;
+    if decoded != input {
+        return Err(format!("expected file decodes to {} bytes, input has {}", decoded.len(), input.len()));
+    }
+    if let Some(metadata) = &case.options.metadata {
+        let recorded = read_metadata(&expected).map_err(|e| format!("cannot read metadata: {}", e))?;
+        let wanted = Metadata { size: Some(input.len() as u64), ..metadata.clone() };
+        if recorded.as_ref() != Some(&wanted) {
+            return Err(format!("expected file records {:?}, manifest says {:?}", recorded, wanted));
+        }
+    }
+    if compressed != expected {
+        let offset = compressed.iter().zip(expected.iter()).position(|(a, b)| a != b)
+            .unwrap_or(compressed.len().min(expected.len()));
+        return Err(format!("encoder output differs at byte {} ({} bytes, expected {})", offset, compressed.len(), expected.len()));
+    }
+    Ok(false)
+}
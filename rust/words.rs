@@ -0,0 +1,187 @@
+// 以词为单位的字母表：字母数字组成的词、连续的空白、单个标点各算一个符号。
+// 每个符号在字典里有一个编号，编号再映射成 char，
+// 这样后面的 Huffman 树和编码可以原样使用。
+// 出现次数少的词不进字典，拆成单个字符来编码（转义），
+// 所以字典里总是包含这些词用到的单个字符。
+
+use std::collections::HashMap;
+
+use super::Error;
+
+// 少于这么多次的词单独占一个符号不划算
+const MIN_WORD_COUNT: usize = 4;
+// 编号映射成 char，跳过代理区之后一共只有这么多个，字典不能超过它
+const MAX_IDS: usize = 0x110000 - 0x800;
+
+fn class(ch: char) -> u8 {
+    if ch.is_alphanumeric() {
+        0
+    } else if ch.is_whitespace() {
+        1
+    } else {
+        2
+    }
+}
+
+fn tokenize(contents: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut last_class = None;
+    for (i, ch) in contents.char_indices() {
+        let current = class(ch);
+        // 标点不合并，词和空白合并成一串
+        if last_class.is_some() && (last_class != Some(current) || current == 2) {
+            tokens.push(&contents[start..i]);
+            start = i;
+        }
+        last_class = Some(current);
+    }
+    if start < contents.len() {
+        tokens.push(&contents[start..]);
+    }
+    tokens
+}
+
+// 编号跳过代理区，保证映射出来的都是合法的 char
+fn id_to_char(id: usize) -> Result<char, Error> {
+    let id = u32::try_from(id).map_err(|_| Error::Limit("Too many distinct words"))?;
+    char::from_u32(if id >= 0xD800 { id + 0x800 } else { id }).ok_or(Error::Limit("Too many distinct words"))
+}
+
+fn char_to_id(ch: char) -> usize {
+    let id = ch as u32;
+    (if id >= 0xE000 { id - 0x800 } else { id }) as usize
+}
+
+// 返回 (字典, 编号组成的字符串)
+pub fn encode(contents: &str) -> Result<(Vec<String>, String), Error> {
+    let tokens = tokenize(contents);
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for token in tokens.iter() {
+        *counts.entry(token).or_insert(0) += 1;
+    }
+
+    let mut dictionary: Vec<String> = Vec::new();
+    for (token, count) in counts.iter() {
+        if *count >= MIN_WORD_COUNT || token.chars().count() == 1 {
+            dictionary.push(token.to_string());
+        } else {
+            dictionary.extend(token.chars().map(String::from));
+        }
+    }
+    // 排好序之后相邻的词前缀相同，字典可以用前缀压缩存储
+    dictionary.sort();
+    dictionary.dedup();
+    if dictionary.len() > MAX_IDS {
+        dictionary = cap_dictionary(&counts);
+    }
+
+    let ids: HashMap<&str, usize> = dictionary.iter().enumerate().map(|(id, word)| (word.as_str(), id)).collect();
+    let mut encoded = String::new();
+    for token in tokens {
+        match ids.get(token) {
+            Some(&id) => encoded.push(id_to_char(id)?),
+            None => {
+                let mut buf = [0; 4];
+                for ch in token.chars() {
+                    encoded.push(id_to_char(ids[&*ch.encode_utf8(&mut buf)])?);
+                }
+            },
+        }
+    }
+    Ok((dictionary, encoded))
+}
+
+// 编号不够用时，输入里出现的每个字符都进字典（最多 MAX_IDS 个，不会超），
+// 剩下的位置按出现次数从多到少放词，放不下的词拆成单个字符编码
+fn cap_dictionary(counts: &HashMap<&str, usize>) -> Vec<String> {
+    let mut chars: Vec<char> = counts.keys().flat_map(|token| token.chars()).collect();
+    chars.sort_unstable();
+    chars.dedup();
+    let mut words: Vec<(&str, usize)> = counts.iter()
+        .filter(|(token, count)| **count >= MIN_WORD_COUNT && token.chars().count() > 1)
+        .map(|(token, count)| (*token, *count))
+        .collect();
+    // 次数相同时按词排序，同样的输入总是得到同样的字典
+    words.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    words.truncate(MAX_IDS - chars.len());
+
+    let mut dictionary: Vec<String> = chars.into_iter().map(String::from).collect();
+    dictionary.extend(words.into_iter().map(|(word, _)| word.to_string()));
+    dictionary.sort();
+    dictionary
+}
+
+pub fn decode(encoded: &str, dictionary: &[String]) -> Result<String, Error> {
+    let mut decoded = String::new();
+    for ch in encoded.chars() {
+        decoded.push_str(dictionary.get(char_to_id(ch)).ok_or(Error::Corrupt("Unknown word id"))?);
+    }
+    Ok(decoded)
+}
+
+// 格式: 词数(varint) | { 与上一个词相同的前缀字节数(varint) | 剩余字节数(varint) | 剩余字节 }...
+pub fn serialize_dictionary(dictionary: &[String]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, dictionary.len());
+    let mut prev: &[u8] = &[];
+    for word in dictionary {
+        let word = word.as_bytes();
+        let shared = prev.iter().zip(word.iter()).take_while(|(a, b)| a == b).count();
+        write_varint(&mut out, shared);
+        write_varint(&mut out, word.len() - shared);
+        out.extend_from_slice(&word[shared..]);
+        prev = word;
+    }
+    out
+}
+
+pub fn deserialize_dictionary(bytes: &[u8]) -> Result<Vec<String>, Error> {
+    let mut pos = 0;
+    let count = read_varint(bytes, &mut pos)?;
+    let mut dictionary = Vec::new();
+    let mut prev: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let shared = read_varint(bytes, &mut pos)?;
+        let len = read_varint(bytes, &mut pos)?;
+        if shared > prev.len() {
+            return Err(Error::Corrupt("Invalid dictionary"));
+        }
+        let end = pos.checked_add(len).ok_or(Error::Corrupt("Truncated dictionary"))?;
+        let suffix = bytes.get(pos..end).ok_or(Error::Corrupt("Truncated dictionary"))?;
+        pos = end;
+        let mut word = prev[..shared].to_vec();
+        word.extend_from_slice(suffix);
+        dictionary.push(String::from_utf8(word.clone()).map_err(|_| Error::Corrupt("Invalid UTF-8 in dictionary"))?);
+        prev = word;
+    }
+    if pos != bytes.len() {
+        return Err(Error::Corrupt("Trailing data after dictionary"));
+    }
+    Ok(dictionary)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, Error> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or(Error::Corrupt("Truncated varint"))?;
+        *pos += 1;
+        if shift >= usize::BITS {
+            return Err(Error::Corrupt("Varint too long"));
+        }
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}